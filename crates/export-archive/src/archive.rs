@@ -0,0 +1,306 @@
+//! The [`ExportArchive`] itself.
+
+use moho_types::{ExportContainer, ExportEntryProof, ExportState, ExportStateError, MohoState};
+use strata_merkle::{MerkleProofB32, Mmr, Mmr64B32, MmrState, Sha256Hasher as MerkleHasher};
+
+use crate::{ArchiveError, ArchiveStorage};
+
+type Hash32 = [u8; 32];
+
+/// Retains the leaves of every export container so that MMR inclusion proofs can be served for
+/// any entry against any historical [`ExportState`].
+#[derive(Debug)]
+pub struct ExportArchive<S> {
+    storage: S,
+}
+
+impl<S: ArchiveStorage> ExportArchive<S> {
+    /// Creates an archive on top of the given storage backend.
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Returns a reference to the storage backend.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Consumes self and returns the storage backend.
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+
+    /// Adds an entry to the export state and records it in the archive.
+    ///
+    /// The entry is only archived if the export state accepts it, and only added to the export
+    /// state once it is archived, so that a failure on either side leaves both unchanged. The
+    /// archive must hold exactly the container's entries beforehand, otherwise
+    /// [`ArchiveError::LeafCountMismatch`] is returned.
+    pub fn add_entry(
+        &mut self,
+        state: &mut ExportState,
+        container_id: u8,
+        entry: Hash32,
+    ) -> Result<(), ArchiveError<S::Error>> {
+        // Check that the container accepts the entry on a copy before archiving it.
+        let mut container = state
+            .get_container(container_id)
+            .cloned()
+            .unwrap_or_else(|| ExportContainer::new(container_id));
+        let expected = container.entries_mmr().num_entries();
+        container.add_entry(entry)?;
+
+        // Refuse to extend an archive that no longer mirrors the container, as its leaves would
+        // not reproduce the MMR.
+        let stored = self
+            .storage
+            .leaf_count(container_id)
+            .map_err(ArchiveError::Storage)?;
+        if stored != expected {
+            return Err(ArchiveError::LeafCountMismatch {
+                container_id,
+                stored,
+                expected,
+            });
+        }
+
+        self.record_entry(container_id, entry)?;
+        state
+            .add_entry(container_id, entry)
+            .expect("entry accepted by a copy of the container");
+        Ok(())
+    }
+
+    /// Records an entry that was added to an export state outside of the archive.
+    ///
+    /// Entries must be recorded in the same order they were added to the export state.
+    pub fn record_entry(
+        &mut self,
+        container_id: u8,
+        entry: Hash32,
+    ) -> Result<(), ArchiveError<S::Error>> {
+        self.storage
+            .append_leaf(container_id, entry)
+            .map_err(ArchiveError::Storage)
+    }
+
+    /// Creates an MMR inclusion proof of entry `index` of container `container_id` against that
+    /// container's MMR in `state`.
+    ///
+    /// `state` may be any version of the export state the archive has mirrored, not only the
+    /// latest one.
+    pub fn prove_entry(
+        &self,
+        state: &ExportState,
+        container_id: u8,
+        index: u64,
+    ) -> Result<MerkleProofB32, ArchiveError<S::Error>> {
+        let container = state
+            .get_container(container_id)
+            .ok_or(ExportStateError::ContainerNotFound(container_id))?;
+
+        let entries = container.entries_mmr().num_entries();
+        if index >= entries {
+            return Err(ArchiveError::EntryOutOfRange {
+                container_id,
+                index,
+                entries,
+            });
+        }
+
+        let leaves = self
+            .storage
+            .leaves(container_id, entries)
+            .map_err(ArchiveError::Storage)?;
+        if (leaves.len() as u64) < entries {
+            return Err(ArchiveError::LeafCountMismatch {
+                container_id,
+                stored: leaves.len() as u64,
+                expected: entries,
+            });
+        }
+
+        // Replay the leaves into a fresh MMR, tracking the proof of the requested entry as later
+        // leaves are added.
+        let mut mmr = <Mmr64B32 as MmrState<Hash32>>::new_empty();
+        let mut proofs = Vec::with_capacity(1);
+        for (i, leaf) in leaves.into_iter().enumerate() {
+            let proof =
+                Mmr::<MerkleHasher>::add_leaf_updating_proof_list(&mut mmr, leaf, &mut proofs)
+                    .map_err(ArchiveError::Mmr)?;
+            if i as u64 == index {
+                proofs.push(proof);
+            }
+        }
+
+        if &mmr != container.entries_mmr() {
+            return Err(ArchiveError::HistoryMismatch(container_id));
+        }
+
+        let proof = proofs.pop().expect("index is within replayed leaves");
+        Ok(MerkleProofB32::from_generic(&proof))
+    }
+
+    /// Creates an [`ExportEntryProof`] of entry `index` of container `container_id` against the
+    /// commitment of `state`.
+    pub fn prove_entry_in_state(
+        &self,
+        state: &MohoState,
+        container_id: u8,
+        index: u64,
+    ) -> Result<ExportEntryProof, ArchiveError<S::Error>> {
+        let entry_proof = self.prove_entry(state.export_state(), container_id, index)?;
+        Ok(ExportEntryProof::new(state, container_id, entry_proof)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moho_types::{InnerStateCommitment, MohoState, SpecCommitment, test_utils::always_accept};
+
+    use super::*;
+    use crate::MemoryStorage;
+
+    fn entry(container_id: u8, i: u8) -> Hash32 {
+        let mut entry = [i; 32];
+        entry[0] = container_id;
+        entry
+    }
+
+    #[test]
+    fn test_prove_against_historical_versions() {
+        let mut archive = ExportArchive::new(MemoryStorage::new());
+        let mut state = ExportState::new(vec![]).unwrap();
+        let mut history = vec![state.clone()];
+
+        for i in 0..7 {
+            archive.add_entry(&mut state, 1, entry(1, i)).unwrap();
+            if i % 2 == 0 {
+                archive.add_entry(&mut state, 2, entry(2, i)).unwrap();
+            }
+            history.push(state.clone());
+        }
+
+        for version in &history {
+            for container in version.containers() {
+                let id = container.container_id();
+                for index in 0..container.entries_mmr().num_entries() {
+                    let leaf = archive.storage().leaves(id, index + 1).unwrap()[index as usize];
+                    let proof = archive.prove_entry(version, id, index).unwrap();
+                    assert!(Mmr::<MerkleHasher>::verify(
+                        container.entries_mmr(),
+                        &proof.to_generic(),
+                        &leaf
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_prove_entry_in_state() {
+        let mut archive = ExportArchive::new(MemoryStorage::new());
+        let mut export_state = ExportState::new(vec![]).unwrap();
+        for i in 0..5 {
+            archive
+                .add_entry(&mut export_state, 3, entry(3, i))
+                .unwrap();
+        }
+
+        let state = MohoState::new(
+            InnerStateCommitment::from([0x22; 32]),
+            always_accept(),
+            export_state,
+            SpecCommitment::default(),
+        );
+        let commitment = state.compute_commitment();
+
+        let proof = archive.prove_entry_in_state(&state, 3, 2).unwrap();
+        assert!(proof.verify(&commitment, 3, &entry(3, 2)));
+        assert!(!proof.verify(&commitment, 3, &entry(3, 1)));
+    }
+
+    /// Storage whose appends always fail, on top of the leaves of a [`MemoryStorage`].
+    #[derive(Debug)]
+    struct FailingStorage(MemoryStorage);
+
+    impl ArchiveStorage for FailingStorage {
+        type Error = &'static str;
+
+        fn append_leaf(&mut self, _container_id: u8, _leaf: Hash32) -> Result<(), Self::Error> {
+            Err("append failed")
+        }
+
+        fn leaf_count(&self, container_id: u8) -> Result<u64, Self::Error> {
+            Ok(self.0.leaf_count(container_id).unwrap())
+        }
+
+        fn leaves(&self, container_id: u8, count: u64) -> Result<Vec<Hash32>, Self::Error> {
+            Ok(self.0.leaves(container_id, count).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_add_entry_storage_failure_leaves_state_unchanged() {
+        let mut archive = ExportArchive::new(MemoryStorage::new());
+        let mut state = ExportState::new(vec![]).unwrap();
+        archive.add_entry(&mut state, 1, entry(1, 0)).unwrap();
+        let before = state.clone();
+
+        let mut archive = ExportArchive::new(FailingStorage(archive.into_storage()));
+
+        assert!(matches!(
+            archive.add_entry(&mut state, 1, entry(1, 1)),
+            Err(ArchiveError::Storage("append failed"))
+        ));
+        assert!(matches!(
+            archive.add_entry(&mut state, 2, entry(2, 0)),
+            Err(ArchiveError::Storage(_))
+        ));
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_prove_errors() {
+        let mut archive = ExportArchive::new(MemoryStorage::new());
+        let mut state = ExportState::new(vec![]).unwrap();
+        archive.add_entry(&mut state, 1, entry(1, 0)).unwrap();
+
+        assert!(matches!(
+            archive.prove_entry(&state, 2, 0),
+            Err(ArchiveError::ExportState(
+                ExportStateError::ContainerNotFound(2)
+            ))
+        ));
+        assert!(matches!(
+            archive.prove_entry(&state, 1, 1),
+            Err(ArchiveError::EntryOutOfRange { index: 1, .. })
+        ));
+
+        // Entries added behind the archive's back cannot be proven, and the archive refuses to
+        // record further entries.
+        state.add_entry(1, entry(1, 1)).unwrap();
+        assert!(matches!(
+            archive.prove_entry(&state, 1, 0),
+            Err(ArchiveError::LeafCountMismatch { stored: 1, .. })
+        ));
+        let before = state.clone();
+        assert!(matches!(
+            archive.add_entry(&mut state, 1, entry(1, 2)),
+            Err(ArchiveError::LeafCountMismatch {
+                container_id: 1,
+                stored: 1,
+                expected: 2,
+            })
+        ));
+        assert_eq!(state, before);
+        assert_eq!(archive.storage().leaf_count(1).unwrap(), 1);
+
+        // Entries recorded in the wrong order do not reproduce the MMR.
+        archive.record_entry(1, entry(1, 2)).unwrap();
+        assert!(matches!(
+            archive.prove_entry(&state, 1, 0),
+            Err(ArchiveError::HistoryMismatch(1))
+        ));
+    }
+}
@@ -0,0 +1,51 @@
+//! Error types for the export archive.
+
+use moho_types::ExportStateError;
+use strata_merkle::MerkleError;
+use thiserror::Error;
+
+/// Errors that can occur when recording entries in or serving proofs from an
+/// [`ExportArchive`](crate::ExportArchive).
+///
+/// Generic over the [`ArchiveStorage::Error`](crate::ArchiveStorage::Error) of the backend.
+#[derive(Debug, Error)]
+pub enum ArchiveError<E> {
+    /// The storage backend failed.
+    #[error("archive storage: {0}")]
+    Storage(E),
+
+    /// The export state rejected the operation.
+    #[error(transparent)]
+    ExportState(#[from] ExportStateError),
+
+    /// The requested entry index is beyond the container's number of entries.
+    #[error("entry {index} out of range for container {container_id} with {entries} entries")]
+    EntryOutOfRange {
+        /// The container the entry was requested from.
+        container_id: u8,
+        /// The requested entry index.
+        index: u64,
+        /// The number of entries in the container.
+        entries: u64,
+    },
+
+    /// The archive does not hold as many leaves for a container as the export state has
+    /// entries, e.g. because entries were added to the export state behind the archive's back.
+    #[error("archive holds {stored} leaves for container {container_id}, expected {expected}")]
+    LeafCountMismatch {
+        /// The container whose leaves do not match.
+        container_id: u8,
+        /// The number of leaves held by the archive.
+        stored: u64,
+        /// The number of entries in the container.
+        expected: u64,
+    },
+
+    /// Replaying the archived leaves does not reproduce the container's MMR.
+    #[error("archived leaves for container {0} do not match the export state")]
+    HistoryMismatch(u8),
+
+    /// Replaying the archived leaves into an MMR failed.
+    #[error("failed to replay archived leaves: {0}")]
+    Mmr(MerkleError),
+}
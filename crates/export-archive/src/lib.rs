@@ -0,0 +1,16 @@
+//! Host-side archive of Moho export container leaves.
+//!
+//! An [`ExportContainer`](moho_types::ExportContainer) only keeps the peaks of its entries MMR, so
+//! once an entry is added nobody can prove its inclusion later. [`ExportArchive`] mirrors every
+//! [`ExportState::add_entry`](moho_types::ExportState::add_entry) call and retains the full leaf
+//! set of each container in an [`ArchiveStorage`] backend. Since containers are append-only, the
+//! first `n` archived leaves of a container are exactly the leaves of any historical version of
+//! it with `n` entries, so the archive can serve MMR proofs against any past export state.
+
+mod archive;
+mod errors;
+mod storage;
+
+pub use archive::ExportArchive;
+pub use errors::ArchiveError;
+pub use storage::{ArchiveStorage, FileStorage, MemoryStorage};
@@ -0,0 +1,206 @@
+//! Storage backends for the export archive.
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+type Hash32 = [u8; 32];
+
+/// Append-only storage of export container leaves, keyed by container ID.
+pub trait ArchiveStorage {
+    /// Error returned by the backend.
+    type Error: fmt::Debug + fmt::Display;
+
+    /// Appends a leaf to the given container.
+    fn append_leaf(&mut self, container_id: u8, leaf: Hash32) -> Result<(), Self::Error>;
+
+    /// Returns the number of leaves stored for the given container.
+    fn leaf_count(&self, container_id: u8) -> Result<u64, Self::Error>;
+
+    /// Returns the first `count` leaves of the given container, or all of them if fewer are
+    /// stored.
+    fn leaves(&self, container_id: u8, count: u64) -> Result<Vec<Hash32>, Self::Error>;
+}
+
+/// In-memory [`ArchiveStorage`].
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    containers: BTreeMap<u8, Vec<Hash32>>,
+}
+
+impl MemoryStorage {
+    /// Creates an empty in-memory storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ArchiveStorage for MemoryStorage {
+    type Error = Infallible;
+
+    fn append_leaf(&mut self, container_id: u8, leaf: Hash32) -> Result<(), Self::Error> {
+        self.containers.entry(container_id).or_default().push(leaf);
+        Ok(())
+    }
+
+    fn leaf_count(&self, container_id: u8) -> Result<u64, Self::Error> {
+        Ok(self
+            .containers
+            .get(&container_id)
+            .map_or(0, |l| l.len() as u64))
+    }
+
+    fn leaves(&self, container_id: u8, count: u64) -> Result<Vec<Hash32>, Self::Error> {
+        let leaves = self
+            .containers
+            .get(&container_id)
+            .map_or(&[][..], |l| l.as_slice());
+        let count = leaves.len().min(count as usize);
+        Ok(leaves[..count].to_vec())
+    }
+}
+
+/// File-backed [`ArchiveStorage`].
+///
+/// Stores the leaves of each container as raw concatenated 32-byte hashes in a separate file
+/// under a base directory.
+///
+/// An append interrupted midway can leave a torn leaf at the end of a file. Torn leaves are
+/// ignored when reading and truncated away by the next append to the container.
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    /// Opens the storage rooted at `dir`, creating the directory if it does not exist.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the directory the storage is rooted at.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn container_path(&self, container_id: u8) -> PathBuf {
+        self.dir.join(format!("container-{container_id:03}.leaves"))
+    }
+}
+
+impl ArchiveStorage for FileStorage {
+    type Error = io::Error;
+
+    fn append_leaf(&mut self, container_id: u8, leaf: Hash32) -> Result<(), Self::Error> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.container_path(container_id))?;
+
+        // Drop a torn leaf left by an interrupted append, so that the new leaf starts at a leaf
+        // boundary.
+        let len = file.metadata()?.len();
+        if len % 32 != 0 {
+            file.set_len(len - len % 32)?;
+        }
+
+        file.write_all(&leaf)?;
+        file.sync_data()
+    }
+
+    fn leaf_count(&self, container_id: u8) -> Result<u64, Self::Error> {
+        match fs::metadata(self.container_path(container_id)) {
+            Ok(meta) => Ok(meta.len() / 32),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn leaves(&self, container_id: u8, count: u64) -> Result<Vec<Hash32>, Self::Error> {
+        let file = match File::open(self.container_path(container_id)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut buf = Vec::new();
+        file.take(count.saturating_mul(32)).read_to_end(&mut buf)?;
+        Ok(buf
+            .chunks_exact(32)
+            .map(|chunk| chunk.try_into().expect("chunk is 32 bytes"))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise<S: ArchiveStorage>(storage: &mut S) {
+        assert_eq!(storage.leaf_count(1).unwrap(), 0);
+        assert!(storage.leaves(1, 10).unwrap().is_empty());
+
+        storage.append_leaf(1, [0xA1; 32]).unwrap();
+        storage.append_leaf(2, [0xB1; 32]).unwrap();
+        storage.append_leaf(1, [0xA2; 32]).unwrap();
+
+        assert_eq!(storage.leaf_count(1).unwrap(), 2);
+        assert_eq!(storage.leaf_count(2).unwrap(), 1);
+        assert_eq!(storage.leaves(1, 1).unwrap(), vec![[0xA1; 32]]);
+        assert_eq!(storage.leaves(1, 10).unwrap(), vec![[0xA1; 32], [0xA2; 32]]);
+        assert_eq!(storage.leaves(2, 10).unwrap(), vec![[0xB1; 32]]);
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        exercise(&mut MemoryStorage::new());
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let dir = std::env::temp_dir().join(format!("moho-archive-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut storage = FileStorage::open(&dir).unwrap();
+        exercise(&mut storage);
+
+        // Leaves survive reopening the storage.
+        let reopened = FileStorage::open(&dir).unwrap();
+        assert_eq!(reopened.leaf_count(1).unwrap(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_storage_drops_torn_leaf() {
+        let dir =
+            std::env::temp_dir().join(format!("moho-archive-torn-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut storage = FileStorage::open(&dir).unwrap();
+        storage.append_leaf(1, [0xA1; 32]).unwrap();
+
+        // Simulate an append interrupted after writing part of a leaf.
+        OpenOptions::new()
+            .append(true)
+            .open(storage.container_path(1))
+            .unwrap()
+            .write_all(&[0xFF; 5])
+            .unwrap();
+        assert_eq!(storage.leaf_count(1).unwrap(), 1);
+        assert_eq!(storage.leaves(1, 10).unwrap(), vec![[0xA1; 32]]);
+
+        storage.append_leaf(1, [0xA2; 32]).unwrap();
+        assert_eq!(storage.leaf_count(1).unwrap(), 2);
+        assert_eq!(storage.leaves(1, 10).unwrap(), vec![[0xA1; 32], [0xA2; 32]]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,10 @@
-use moho_types::{ChainError, RecursiveMohoAttestation, StepMohoAttestation};
-use strata_predicate::PredicateError;
+use alloc::boxed::Box;
+
+use moho_types::{
+    ChainError, MohoStateCommitment, RecursiveMohoAttestation, StateRefAttestation,
+    StepMohoAttestation,
+};
+use strata_predicate::{PredicateError, PredicateKey};
 use thiserror::Error;
 
 /// Errors that can occur when working with Moho state transitions.
@@ -25,6 +30,23 @@ pub enum MohoError {
     /// A Merkle inclusion proof is invalid.
     #[error("invalid merkle proof")]
     InvalidMerkleProof,
+
+    /// A chain input contains no step proofs.
+    #[error("no step proofs to chain")]
+    EmptyStepBatch,
+
+    /// The resulting recursive attestation does not start at the configured genesis.
+    #[error("{0}")]
+    GenesisMismatch(#[source] Box<GenesisMismatchError>),
+
+    /// The previous recursive proof was produced under a moho predicate that is not an allowed
+    /// predecessor.
+    #[error("previous recursive proof was produced under disallowed moho predicate {0:?}")]
+    DisallowedPredecessor(Box<PredicateKey>),
+
+    /// A Moho state does not match the proven state of a recursive attestation.
+    #[error("{0}")]
+    ProvenStateMismatch(#[source] Box<ProvenStateMismatchError>),
 }
 
 impl From<ChainError> for MohoError {
@@ -48,3 +70,17 @@ pub struct InvalidRecursiveProofError {
     #[source]
     pub source: PredicateError,
 }
+
+#[derive(Debug, Error)]
+#[error("genesis mismatch: expected {expected}, got {actual}")]
+pub struct GenesisMismatchError {
+    pub expected: StateRefAttestation,
+    pub actual: StateRefAttestation,
+}
+
+#[derive(Debug, Error)]
+#[error("proven state mismatch: expected {expected}, got {actual}")]
+pub struct ProvenStateMismatchError {
+    pub expected: MohoStateCommitment,
+    pub actual: MohoStateCommitment,
+}
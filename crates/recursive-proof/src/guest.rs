@@ -0,0 +1,116 @@
+//! zkVM entry points for the recursive proof, and for consumer guests reading verified export
+//! state.
+
+use alloc::vec::Vec;
+
+use moho_types::{ExportState, MohoInstanceId, MohoState, RecursiveMohoProof, StateRefAttestation};
+use ssz::{Decode, Encode};
+use strata_predicate::PredicateKey;
+use zkaleido::ZkVmEnv;
+
+use crate::{
+    MohoError, MohoRecursiveInput, MohoRecursiveOutput, MohoWrapInput,
+    verify_and_chain_with_predecessors, verify_export_state, verify_recursive_moho_proof,
+};
+
+/// Reads an SSZ-encoded [`MohoRecursiveInput`] from the zkVM, verifies and chains the proof,
+/// and commits the resulting [`MohoRecursiveOutput`] back to the zkVM.
+///
+/// # Panics
+///
+/// Panics if decoding the input or verifying/chaining the proof fails.
+pub fn process_recursive_moho_proof(zkvm: &impl ZkVmEnv) {
+    process_recursive_moho_proof_with_predecessors(zkvm, &[]);
+}
+
+/// Like [`process_recursive_moho_proof`], but also accepts a previous recursive proof produced
+/// under one of the `allowed` predecessors of the moho predicate.
+///
+/// Upgraded recursive programs hardcode the predicate keys of the programs they replace as
+/// `allowed`, so that they can extend the existing chain of proofs rather than restart it. The
+/// committed output records the predecessor in its lineage.
+///
+/// # Panics
+///
+/// Panics if decoding the input or verifying/chaining the proof fails.
+pub fn process_recursive_moho_proof_with_predecessors(
+    zkvm: &impl ZkVmEnv,
+    allowed: &[PredicateKey],
+) {
+    let input: MohoRecursiveInput = read_ssz(zkvm);
+
+    let moho_predicate = input.moho_predicate().clone();
+    let instance = *input.instance();
+    let lineage = input.lineage();
+    let attestation = verify_and_chain_with_predecessors(input, allowed)
+        .expect("failed to verify and chain moho proof");
+    let output =
+        MohoRecursiveOutput::new(instance, attestation, moho_predicate).with_lineage(lineage);
+
+    zkvm.commit_buf(&output.as_ssz_bytes());
+}
+
+/// Reads an SSZ-encoded [`MohoWrapInput`] from the zkVM, verifies the wrapped recursive proof,
+/// and commits its [`MohoRecursiveOutput`] back to the zkVM unchanged.
+///
+/// The wrapped proof is verified against the moho predicate key given in the input, so the
+/// output only attests to a trusted recursive program once checked with
+/// [`verify_wrapped_moho_proof`](crate::verify_wrapped_moho_proof).
+///
+/// # Panics
+///
+/// Panics if decoding the input or verifying the proof fails.
+pub fn process_moho_wrap(zkvm: &impl ZkVmEnv) {
+    let input: MohoWrapInput = read_ssz(zkvm);
+
+    let attestation = verify_recursive_moho_proof(
+        input.proof,
+        &input.moho_predicate,
+        &input.instance,
+        &input.lineage,
+        None,
+    )
+    .expect("failed to verify wrapped moho proof");
+    let output = MohoRecursiveOutput::new(input.instance, attestation, input.moho_predicate)
+        .with_lineage(input.lineage);
+
+    zkvm.commit_buf(&output.as_ssz_bytes());
+}
+
+/// Reads an SSZ-encoded [`RecursiveMohoProof`], the SSZ-encoded lineage it reports and the
+/// SSZ-encoded [`MohoState`] it proves from the zkVM, and returns the export state once all are
+/// verified.
+///
+/// Meant to be called from consumer guests, which pass the moho predicate key, instance and
+/// genesis they trust. See [`verify_export_state`] for the checks performed.
+///
+/// # Errors
+///
+/// Returns a [`MohoError`] if the proof or the proven state fails verification.
+///
+/// # Panics
+///
+/// Panics if decoding the proof or the state fails.
+pub fn read_verified_export_state(
+    zkvm: &impl ZkVmEnv,
+    moho_predicate: &PredicateKey,
+    instance: &MohoInstanceId,
+    expected_genesis: Option<&StateRefAttestation>,
+) -> Result<ExportState, MohoError> {
+    let proof: RecursiveMohoProof = read_ssz(zkvm);
+    let lineage: Vec<PredicateKey> = read_ssz(zkvm);
+    let state: MohoState = read_ssz(zkvm);
+    verify_export_state(
+        proof,
+        state,
+        moho_predicate,
+        instance,
+        &lineage,
+        expected_genesis,
+    )
+}
+
+/// Reads the next input buffer from the zkVM and decodes it as SSZ.
+fn read_ssz<T: Decode>(zkvm: &impl ZkVmEnv) -> T {
+    T::from_ssz_bytes(&zkvm.read_buf()).unwrap_or_else(|e| panic!("guest: decode input: {e:?}"))
+}
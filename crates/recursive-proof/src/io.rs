@@ -1,56 +1,256 @@
-use moho_types::{RecursiveMohoAttestation, RecursiveMohoProof, StepMohoProof};
+use alloc::{vec, vec::Vec};
+
+use moho_types::{
+    ClaimDomain, ClaimKind, MohoInstanceId, RecursiveMohoAttestation, RecursiveMohoProof,
+    StateRefAttestation, StepMohoProof,
+};
 use ssz_derive::{Decode, Encode};
 use strata_merkle::MerkleProofB32;
 use strata_predicate::PredicateKey;
 
 /// Input data for generating a recursive Moho proof.
 ///
-/// Contains all the components needed to create a new recursive proof by combining a previous
-/// recursive proof (if any) with a new step proof. The recursive proof is extended by verifying
-/// both proofs and checking that they are continuous.
+/// A recursive proof is produced either by extending a previous recursive proof (if any) with a
+/// batch of step proofs, or by merging two recursive proofs covering adjacent ranges. The latter
+/// allows step proofs to be aggregated in parallel as a binary tree.
+///
+/// Both variants carry the [`MohoInstanceId`] all proofs are bound to and the configured genesis
+/// the resulting recursive proof must start at.
 #[derive(Debug, Clone, Encode, Decode)]
-pub struct MohoRecursiveInput {
-    /// Predicate key for verifying the previous recursive proof.
-    pub(crate) moho_predicate: PredicateKey,
-    /// Previous recursive proof to extend, or `None` for the base case (first step in the chain).
-    pub(crate) prev_recursive_proof: Option<RecursiveMohoProof>,
-    /// Predicate key for verifying the incremental step proof.
-    pub(crate) step_predicate: PredicateKey,
-    /// The step proof attesting to the next state transition to be verified and chained.
-    pub(crate) incremental_step_proof: StepMohoProof,
-    /// Merkle proof that `step_predicate` is included in the step proof's starting state
-    /// commitment.
-    pub(crate) step_predicate_merkle_proof: MerkleProofB32,
+#[ssz(enum_behaviour = "union")]
+pub enum MohoRecursiveInput {
+    /// Extend a recursive proof with a batch of step proofs.
+    Chain(MohoChainInput),
+    /// Merge two recursive proofs covering adjacent ranges.
+    Aggregate(MohoAggregateInput),
 }
 
 impl MohoRecursiveInput {
-    /// Creates a new [`MohoRecursiveInput`].
+    /// Creates a new [`MohoRecursiveInput::Chain`] input extending `prev_recursive_proof` by a
+    /// single step proof.
     pub fn new(
         moho_predicate: PredicateKey,
+        instance: MohoInstanceId,
+        genesis: StateRefAttestation,
         prev_recursive_proof: Option<RecursiveMohoProof>,
         incremental_step_proof: StepMohoProof,
         step_predicate: PredicateKey,
         step_predicate_merkle_proof: MerkleProofB32,
     ) -> Self {
-        Self {
-            moho_predicate,
-            prev_recursive_proof,
+        let step = MohoStepProofInput::new(
             incremental_step_proof,
             step_predicate,
             step_predicate_merkle_proof,
+        );
+        Self::Chain(MohoChainInput::new(
+            moho_predicate,
+            instance,
+            genesis,
+            prev_recursive_proof,
+            vec![step],
+        ))
+    }
+
+    /// Returns the moho predicate key the resulting recursive proof is produced under.
+    pub fn moho_predicate(&self) -> &PredicateKey {
+        match self {
+            Self::Chain(input) => input.moho_predicate(),
+            Self::Aggregate(input) => input.moho_predicate(),
         }
     }
 
-    /// Returns the moho predicate key used to verify the previous recursive proof.
+    /// Returns the Moho instance all proofs are bound to.
+    pub fn instance(&self) -> &MohoInstanceId {
+        match self {
+            Self::Chain(input) => input.instance(),
+            Self::Aggregate(input) => input.instance(),
+        }
+    }
+
+    /// Returns the genesis the resulting recursive proof must start at.
+    pub fn genesis(&self) -> &StateRefAttestation {
+        match self {
+            Self::Chain(input) => input.genesis(),
+            Self::Aggregate(input) => input.genesis(),
+        }
+    }
+
+    /// Returns the lineage committed by the resulting recursive proof.
+    pub fn lineage(&self) -> Vec<PredicateKey> {
+        match self {
+            Self::Chain(input) => input.lineage(),
+            Self::Aggregate(input) => input.lineage().to_vec(),
+        }
+    }
+}
+
+impl From<MohoChainInput> for MohoRecursiveInput {
+    fn from(input: MohoChainInput) -> Self {
+        Self::Chain(input)
+    }
+}
+
+impl From<MohoAggregateInput> for MohoRecursiveInput {
+    fn from(input: MohoAggregateInput) -> Self {
+        Self::Aggregate(input)
+    }
+}
+
+/// Input for extending a recursive Moho proof with a batch of step proofs.
+///
+/// Contains all the components needed to create a new recursive proof by combining a previous
+/// recursive proof (if any) with an ordered, non-empty list of step proofs. The recursive proof
+/// is extended by verifying all proofs and checking that they are continuous. Batching several
+/// steps amortises the cost of recursion, e.g. when catching up after downtime.
+///
+/// The previous recursive proof may have been produced under a predecessor of the moho predicate,
+/// in which case the resulting proof appends that predecessor to its lineage (see
+/// [`Self::with_predecessor`]).
+///
+/// The `genesis` is provided by the prover, so checking the chain against it in the guest only
+/// proves that the chain is self-consistent. Verifiers must pass the genesis they trust as
+/// `expected_genesis` to [`verify_recursive_moho_proof`](crate::verify_recursive_moho_proof) to
+/// bind the proof to the instance's actual genesis.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct MohoChainInput {
+    /// Predicate key the resulting recursive proof is produced under, and for verifying the
+    /// previous recursive proof unless `prev_moho_predicate` is set.
+    pub(crate) moho_predicate: PredicateKey,
+    /// Moho instance the step and recursive proofs are bound to.
+    pub(crate) instance: MohoInstanceId,
+    /// Genesis the chain must start at: the first step's starting state in the base case, or the
+    /// previous recursive proof's genesis otherwise. Chosen by the prover, see above.
+    pub(crate) genesis: StateRefAttestation,
+    /// Previous recursive proof to extend, or `None` for the base case (first step in the chain).
+    pub(crate) prev_recursive_proof: Option<RecursiveMohoProof>,
+    /// The step proofs to be verified and chained, in order.
+    pub(crate) steps: Vec<MohoStepProofInput>,
+    /// Predecessor predicate key the previous recursive proof was produced under, or `None` if
+    /// it was produced under `moho_predicate`.
+    pub(crate) prev_moho_predicate: Option<PredicateKey>,
+    /// Lineage committed by the previous recursive proof.
+    pub(crate) prev_lineage: Vec<PredicateKey>,
+}
+
+impl MohoChainInput {
+    /// Creates a new [`MohoChainInput`].
+    pub fn new(
+        moho_predicate: PredicateKey,
+        instance: MohoInstanceId,
+        genesis: StateRefAttestation,
+        prev_recursive_proof: Option<RecursiveMohoProof>,
+        steps: Vec<MohoStepProofInput>,
+    ) -> Self {
+        Self {
+            moho_predicate,
+            instance,
+            genesis,
+            prev_recursive_proof,
+            steps,
+            prev_moho_predicate: None,
+            prev_lineage: Vec::new(),
+        }
+    }
+
+    /// Sets the moho predicate the previous recursive proof was produced under and the lineage
+    /// it committed to.
+    ///
+    /// If `prev_moho_predicate` differs from the moho predicate, it must be an allowed
+    /// predecessor of the recursive program, see
+    /// [`process_recursive_moho_proof_with_predecessors`](crate::process_recursive_moho_proof_with_predecessors).
+    pub fn with_predecessor(
+        mut self,
+        prev_moho_predicate: PredicateKey,
+        prev_lineage: Vec<PredicateKey>,
+    ) -> Self {
+        self.prev_moho_predicate = Some(prev_moho_predicate);
+        self.prev_lineage = prev_lineage;
+        self
+    }
+
+    /// Returns the moho predicate key the resulting recursive proof is produced under.
     pub fn moho_predicate(&self) -> &PredicateKey {
         &self.moho_predicate
     }
 
+    /// Returns the moho predicate key used to verify the previous recursive proof.
+    pub fn prev_moho_predicate(&self) -> &PredicateKey {
+        self.prev_moho_predicate
+            .as_ref()
+            .unwrap_or(&self.moho_predicate)
+    }
+
+    /// Returns the lineage committed by the previous recursive proof.
+    pub fn prev_lineage(&self) -> &[PredicateKey] {
+        &self.prev_lineage
+    }
+
+    /// Returns the lineage committed by the resulting recursive proof.
+    ///
+    /// This is the lineage of the previous recursive proof, followed by the predicate it was
+    /// produced under if that is a predecessor of the moho predicate. A base case has an empty
+    /// lineage.
+    pub fn lineage(&self) -> Vec<PredicateKey> {
+        if self.prev_recursive_proof.is_none() {
+            return Vec::new();
+        }
+        let mut lineage = self.prev_lineage.clone();
+        if *self.prev_moho_predicate() != self.moho_predicate {
+            lineage.push(self.prev_moho_predicate().clone());
+        }
+        lineage
+    }
+
+    /// Returns the Moho instance the step and recursive proofs are bound to.
+    pub fn instance(&self) -> &MohoInstanceId {
+        &self.instance
+    }
+
+    /// Returns the genesis the chain must start at.
+    pub fn genesis(&self) -> &StateRefAttestation {
+        &self.genesis
+    }
+
     /// Returns the previous recursive moho proof, if any.
     pub fn prev_recursive_proof(&self) -> Option<&RecursiveMohoProof> {
         self.prev_recursive_proof.as_ref()
     }
 
+    /// Returns the step proofs to be chained, in order.
+    pub fn steps(&self) -> &[MohoStepProofInput] {
+        &self.steps
+    }
+}
+
+/// A single step proof within a [`MohoChainInput`].
+///
+/// Each step carries its own predicate and inclusion proof, since the step predicate may be
+/// rotated by any step in the batch.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct MohoStepProofInput {
+    /// Predicate key for verifying the incremental step proof.
+    pub(crate) step_predicate: PredicateKey,
+    /// The step proof attesting to the next state transition to be verified and chained.
+    pub(crate) incremental_step_proof: StepMohoProof,
+    /// Merkle proof that `step_predicate` is included in the step proof's starting state
+    /// commitment.
+    pub(crate) step_predicate_merkle_proof: MerkleProofB32,
+}
+
+impl MohoStepProofInput {
+    /// Creates a new [`MohoStepProofInput`].
+    pub fn new(
+        incremental_step_proof: StepMohoProof,
+        step_predicate: PredicateKey,
+        step_predicate_merkle_proof: MerkleProofB32,
+    ) -> Self {
+        Self {
+            step_predicate,
+            incremental_step_proof,
+            step_predicate_merkle_proof,
+        }
+    }
+
     /// Returns the incremental step proof.
     pub fn incremental_step_proof(&self) -> &StepMohoProof {
         &self.incremental_step_proof
@@ -67,36 +267,237 @@ impl MohoRecursiveInput {
     }
 }
 
+/// Input for merging two recursive Moho proofs covering adjacent ranges.
+///
+/// Both proofs are verified against the same moho predicate and lineage, and the `left` proof's
+/// proven state must match the `right` proof's genesis. Only the `left` proof must start at the
+/// configured genesis; the `right` proof is anchored at its own starting state.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct MohoAggregateInput {
+    /// Predicate key for verifying both recursive proofs.
+    pub(crate) moho_predicate: PredicateKey,
+    /// Moho instance both recursive proofs are bound to.
+    pub(crate) instance: MohoInstanceId,
+    /// Genesis the `left` proof must start at.
+    pub(crate) genesis: StateRefAttestation,
+    /// Recursive proof covering the earlier range.
+    pub(crate) left: RecursiveMohoProof,
+    /// Recursive proof covering the range directly following `left`.
+    pub(crate) right: RecursiveMohoProof,
+    /// Lineage committed by both recursive proofs.
+    pub(crate) lineage: Vec<PredicateKey>,
+}
+
+impl MohoAggregateInput {
+    /// Creates a new [`MohoAggregateInput`].
+    pub fn new(
+        moho_predicate: PredicateKey,
+        instance: MohoInstanceId,
+        genesis: StateRefAttestation,
+        left: RecursiveMohoProof,
+        right: RecursiveMohoProof,
+    ) -> Self {
+        Self {
+            moho_predicate,
+            instance,
+            genesis,
+            left,
+            right,
+            lineage: Vec::new(),
+        }
+    }
+
+    /// Sets the lineage committed by both recursive proofs.
+    pub fn with_lineage(mut self, lineage: Vec<PredicateKey>) -> Self {
+        self.lineage = lineage;
+        self
+    }
+
+    /// Returns the moho predicate key used to verify both recursive proofs.
+    pub fn moho_predicate(&self) -> &PredicateKey {
+        &self.moho_predicate
+    }
+
+    /// Returns the Moho instance both recursive proofs are bound to.
+    pub fn instance(&self) -> &MohoInstanceId {
+        &self.instance
+    }
+
+    /// Returns the genesis the `left` proof must start at.
+    pub fn genesis(&self) -> &StateRefAttestation {
+        &self.genesis
+    }
+
+    /// Returns the recursive proof covering the earlier range.
+    pub fn left(&self) -> &RecursiveMohoProof {
+        &self.left
+    }
+
+    /// Returns the recursive proof covering the later range.
+    pub fn right(&self) -> &RecursiveMohoProof {
+        &self.right
+    }
+
+    /// Returns the lineage committed by both recursive proofs.
+    pub fn lineage(&self) -> &[PredicateKey] {
+        &self.lineage
+    }
+}
+
+/// Input for wrapping a recursive Moho proof into a proof of another type, e.g. to convert a
+/// compressed proof into a Groth16 proof for on-chain verification.
+///
+/// The wrapped proof is verified against the moho predicate and the wrapping proof commits the
+/// same [`MohoRecursiveOutput`], so the attestation is unchanged.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct MohoWrapInput {
+    /// Predicate key for verifying the wrapped recursive proof.
+    pub(crate) moho_predicate: PredicateKey,
+    /// Moho instance the wrapped recursive proof is bound to.
+    pub(crate) instance: MohoInstanceId,
+    /// Recursive proof to wrap.
+    pub(crate) proof: RecursiveMohoProof,
+    /// Lineage committed by the wrapped recursive proof.
+    pub(crate) lineage: Vec<PredicateKey>,
+}
+
+impl MohoWrapInput {
+    /// Creates a new [`MohoWrapInput`].
+    pub fn new(
+        moho_predicate: PredicateKey,
+        instance: MohoInstanceId,
+        proof: RecursiveMohoProof,
+    ) -> Self {
+        Self {
+            moho_predicate,
+            instance,
+            proof,
+            lineage: Vec::new(),
+        }
+    }
+
+    /// Sets the lineage committed by the wrapped recursive proof.
+    pub fn with_lineage(mut self, lineage: Vec<PredicateKey>) -> Self {
+        self.lineage = lineage;
+        self
+    }
+
+    /// Returns the moho predicate key used to verify the wrapped recursive proof.
+    pub fn moho_predicate(&self) -> &PredicateKey {
+        &self.moho_predicate
+    }
+
+    /// Returns the Moho instance the wrapped recursive proof is bound to.
+    pub fn instance(&self) -> &MohoInstanceId {
+        &self.instance
+    }
+
+    /// Returns the recursive proof to wrap.
+    pub fn proof(&self) -> &RecursiveMohoProof {
+        &self.proof
+    }
+
+    /// Returns the lineage committed by the wrapped recursive proof.
+    pub fn lineage(&self) -> &[PredicateKey] {
+        &self.lineage
+    }
+}
+
 /// Public output committed by a recursive Moho proof.
 ///
-/// Contains the attestation (genesis-to-proven chain) and the predicate key used to verify
-/// the recursive proof itself. The predicate is included because it cannot be hardcoded in the
-/// circuit — verifiers need it to confirm the correct predicate was used.
+/// Contains the recursive claim domain, the attestation (genesis-to-proven chain and its step
+/// count) and the predicate key used to verify the recursive proof itself. The predicate is
+/// included because it cannot be hardcoded in the circuit — verifiers need it to confirm the
+/// correct predicate was used.
+///
+/// When the recursive program is upgraded, the new program may extend proofs produced under an
+/// allowed predecessor of its predicate. The lineage records these predecessors, oldest first,
+/// so verifiers can tell which predicates the proven chain went through.
+///
+/// The SSZ encoding of this container is the exact claim passed to the moho predicate, so the
+/// domain keeps a recursive proof of one Moho instance from being accepted by another.
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct MohoRecursiveOutput {
+    /// The recursive domain of the instance the attestation belongs to.
+    pub(crate) domain: ClaimDomain,
     /// The recursive attestation proven by this proof.
     pub(crate) attestation: RecursiveMohoAttestation,
     /// Predicate key committed as public output so verifiers can confirm the correct
     /// predicate was used.
     pub(crate) moho_predicate: PredicateKey,
+    /// Predecessors of `moho_predicate` the proven chain was produced under, oldest first.
+    pub(crate) lineage: Vec<PredicateKey>,
 }
 
 impl MohoRecursiveOutput {
-    /// Creates a new [`MohoRecursiveOutput`].
-    pub fn new(attestation: RecursiveMohoAttestation, moho_predicate: PredicateKey) -> Self {
+    /// Creates a new [`MohoRecursiveOutput`] within the recursive domain of `instance`.
+    pub fn new(
+        instance: MohoInstanceId,
+        attestation: RecursiveMohoAttestation,
+        moho_predicate: PredicateKey,
+    ) -> Self {
         Self {
+            domain: ClaimDomain::new(ClaimKind::Recursive, instance),
             attestation,
             moho_predicate,
+            lineage: Vec::new(),
         }
     }
 
+    /// Sets the predecessors of the moho predicate the proven chain was produced under, oldest
+    /// first.
+    pub fn with_lineage(mut self, lineage: Vec<PredicateKey>) -> Self {
+        self.lineage = lineage;
+        self
+    }
+
+    /// Returns the claim domain committed as public output.
+    pub fn domain(&self) -> &ClaimDomain {
+        &self.domain
+    }
+
+    /// Returns the Moho instance this proof is bound to.
+    pub fn instance(&self) -> &MohoInstanceId {
+        self.domain.instance()
+    }
+
     /// Returns the recursive attestation proven by this proof.
     pub fn attestation(&self) -> &RecursiveMohoAttestation {
         &self.attestation
     }
 
+    /// Returns the genesis this proof is anchored at.
+    ///
+    /// The recursive program rejects any input whose result does not start at the configured
+    /// genesis, so this is the genesis the prover was configured with.
+    pub fn genesis(&self) -> &StateRefAttestation {
+        self.attestation.genesis()
+    }
+
+    /// Returns whether this proof is anchored at `expected`.
+    ///
+    /// Verifiers should check this against a genesis they trust rather than the one reported by
+    /// the prover.
+    pub fn starts_at(&self, expected: &StateRefAttestation) -> bool {
+        self.genesis() == expected
+    }
+
+    /// Returns the number of steps covered by this proof.
+    ///
+    /// Of two proofs from the same genesis, the one covering more steps is the more advanced, so
+    /// a verifier can use this to pick the best proof and reject regressions.
+    pub fn steps(&self) -> u64 {
+        self.attestation.steps()
+    }
+
     /// Returns the predicate key committed as public output.
     pub fn moho_predicate(&self) -> &PredicateKey {
         &self.moho_predicate
     }
+
+    /// Returns the predecessors of the moho predicate the proven chain was produced under, oldest
+    /// first.
+    pub fn lineage(&self) -> &[PredicateKey] {
+        &self.lineage
+    }
 }
@@ -2,17 +2,39 @@
 //!
 //! This crate ties together the key components required to construct, verify,
 //! and commit recursive Moho proofs in a zkVM environment.
+//!
+//! The verifiers, e.g. [`verify_recursive_moho_proof`] and [`verify_export_state`], and the zkVM
+//! entry points, e.g. [`process_recursive_moho_proof`] and [`read_verified_export_state`], support
+//! `no_std` environments with `alloc` when the default `std` feature is disabled. Only the
+//! host-side programs, e.g. `MohoRecursiveProgram`, require `std`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 mod errors;
+mod guest;
 mod io;
-#[cfg(not(target_os = "zkvm"))]
+#[cfg(all(feature = "std", not(target_os = "zkvm")))]
 mod program;
 mod statements;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
+mod verifier;
 
 pub use errors::MohoError;
-pub use io::{MohoRecursiveInput, MohoRecursiveOutput};
-#[cfg(not(target_os = "zkvm"))]
-pub use program::MohoRecursiveProgram;
-pub use statements::{process_recursive_moho_proof, verify_and_chain};
+pub use guest::{
+    process_moho_wrap, process_recursive_moho_proof,
+    process_recursive_moho_proof_with_predecessors, read_verified_export_state,
+};
+pub use io::{
+    MohoAggregateInput, MohoChainInput, MohoRecursiveInput, MohoRecursiveOutput,
+    MohoStepProofInput, MohoWrapInput,
+};
+#[cfg(all(feature = "std", not(target_os = "zkvm")))]
+pub use program::{Compressed, Groth16, MohoRecursiveProgram, MohoWrapProgram, RecursiveProofKind};
+pub use statements::{verify_and_chain, verify_and_chain_with_predecessors};
+pub use verifier::{
+    verify_export_state, verify_proven_state, verify_recursive_moho_proof,
+    verify_wrapped_moho_proof,
+};
@@ -1,14 +1,44 @@
-use zkaleido::{ZkVmProgram, ZkVmResult};
+use std::marker::PhantomData;
+
+use zkaleido::{ProofType, ZkVmProgram, ZkVmResult};
 use zkaleido_native_adapter::NativeHost;
 
-use crate::{MohoRecursiveInput, MohoRecursiveOutput, process_recursive_moho_proof};
+use crate::{
+    MohoRecursiveInput, MohoRecursiveOutput, MohoWrapInput, process_moho_wrap,
+    process_recursive_moho_proof,
+};
+
+/// The type of proof generated by a Moho recursive program.
+pub trait RecursiveProofKind {
+    /// The proof type requested from the zkVM host.
+    const PROOF_TYPE: ProofType;
+}
+
+/// Compressed proofs, cheap to verify recursively.
+///
+/// Suited to intermediate recursive proofs, which are themselves verified by the next
+/// recursive proof.
+#[derive(Copy, Clone, Debug)]
+pub struct Compressed;
+
+impl RecursiveProofKind for Compressed {
+    const PROOF_TYPE: ProofType = ProofType::Compressed;
+}
+
+/// Groth16 proofs, cheap to verify on-chain.
+#[derive(Copy, Clone, Debug)]
+pub struct Groth16;
+
+impl RecursiveProofKind for Groth16 {
+    const PROOF_TYPE: ProofType = ProofType::Groth16;
+}
 
 /// A host-agnostic ZkVM “program” that encapsulates the recursive proof logic
-/// for the Moho protocol.
+/// for the Moho protocol, generating proofs of type `T`.
 #[derive(Debug)]
-pub struct MohoRecursiveProgram;
+pub struct MohoRecursiveProgram<T: RecursiveProofKind = Groth16>(PhantomData<T>);
 
-impl ZkVmProgram for MohoRecursiveProgram {
+impl<T: RecursiveProofKind> ZkVmProgram for MohoRecursiveProgram<T> {
     type Input = MohoRecursiveInput;
     type Output = MohoRecursiveOutput;
 
@@ -17,7 +47,7 @@ impl ZkVmProgram for MohoRecursiveProgram {
     }
 
     fn proof_type() -> zkaleido::ProofType {
-        zkaleido::ProofType::Groth16
+        T::PROOF_TYPE
     }
 
     fn prepare_input<'a, B>(input: &'a Self::Input) -> zkaleido::ZkVmInputResult<B::Input>
@@ -37,7 +67,7 @@ impl ZkVmProgram for MohoRecursiveProgram {
     }
 }
 
-impl MohoRecursiveProgram {
+impl<T: RecursiveProofKind> MohoRecursiveProgram<T> {
     /// Returns the native host for the moho recursive program
     pub fn native_host() -> NativeHost {
         NativeHost::new_with_random_key(process_recursive_moho_proof)
@@ -54,10 +84,73 @@ impl MohoRecursiveProgram {
     }
 }
 
+/// A host-agnostic ZkVM “program” wrapping a recursive Moho proof into a Groth16 proof.
+///
+/// Converts a proof of a [`MohoRecursiveProgram<Compressed>`] into a Groth16 proof for on-chain
+/// verification. The Groth16 proof commits the same [`MohoRecursiveOutput`] as the wrapped proof,
+/// including the predicate key of the recursive program, so it attests to the same claim under
+/// the predicate key of this program.
+///
+/// The wrapped proof is verified against whichever recursive predicate key the input names, so
+/// verifiers must check Groth16 proofs of this program with
+/// [`verify_wrapped_moho_proof`](crate::verify_wrapped_moho_proof), which binds the claim to the
+/// recursive predicate key they trust.
+#[derive(Debug)]
+pub struct MohoWrapProgram;
+
+impl ZkVmProgram for MohoWrapProgram {
+    type Input = MohoWrapInput;
+    type Output = MohoRecursiveOutput;
+
+    fn name() -> String {
+        "Moho Wrap".to_string()
+    }
+
+    fn proof_type() -> zkaleido::ProofType {
+        Groth16::PROOF_TYPE
+    }
+
+    fn prepare_input<'a, B>(input: &'a Self::Input) -> zkaleido::ZkVmInputResult<B::Input>
+    where
+        B: zkaleido::ZkVmInputBuilder<'a>,
+    {
+        B::new().write_ssz(&input)?.build()
+    }
+
+    fn process_output<H>(
+        public_values: &zkaleido::PublicValues,
+    ) -> zkaleido::ZkVmResult<Self::Output>
+    where
+        H: zkaleido::ZkVmHost,
+    {
+        H::extract_ssz_public_output(public_values)
+    }
+}
+
+impl MohoWrapProgram {
+    /// Returns the native host for the moho wrap program
+    pub fn native_host() -> NativeHost {
+        NativeHost::new_with_random_key(process_moho_wrap)
+    }
+
+    /// Executes the moho wrap program in the native mode
+    pub fn execute(
+        input: &<Self as ZkVmProgram>::Input,
+    ) -> ZkVmResult<<Self as ZkVmProgram>::Output> {
+        let host = Self::native_host();
+        let summary = <Self as ZkVmProgram>::execute(input, &host)?;
+        <Self as ZkVmProgram>::process_output::<NativeHost>(summary.public_values())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use k256::schnorr::signature::Signer;
+    use moho_types::RecursiveMohoProof;
+    use ssz::ssz_encode;
+
     use super::*;
-    use crate::test_utils::*;
+    use crate::{MohoAggregateInput, MohoError, test_utils::*, verify_wrapped_moho_proof};
 
     #[test]
     fn test_execute_base_case() {
@@ -65,7 +158,8 @@ mod tests {
         let step = SchnorrPredicate::new_random();
 
         let input = create_input(1, 2, None, &moho, &step);
-        let output = MohoRecursiveProgram::execute(&input).unwrap();
+        let output = <MohoRecursiveProgram>::execute(&input.into()).unwrap();
+        assert!(output.starts_at(&genesis(1, &step)));
 
         let expected = expected_attestation(1, 2, &step.predicate);
         assert_eq!(*output.attestation().genesis(), *expected.from());
@@ -78,11 +172,126 @@ mod tests {
         let step = SchnorrPredicate::new_random();
 
         let input = create_input(2, 3, Some((1, 2)), &moho, &step);
-        let output = MohoRecursiveProgram::execute(&input).unwrap();
+        let output = <MohoRecursiveProgram>::execute(&input.into()).unwrap();
+
+        let first = expected_attestation(1, 2, &step.predicate);
+        let second = expected_attestation(2, 3, &step.predicate);
+        assert_eq!(*output.attestation().genesis(), *first.from());
+        assert_eq!(*output.attestation().proven(), *second.to());
+    }
+
+    #[test]
+    fn test_execute_aggregate() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let input = MohoAggregateInput::new(
+            moho.predicate.clone(),
+            test_instance(),
+            genesis(1, &step),
+            create_recursive_proof(1, 2, &moho, &step),
+            create_recursive_proof(2, 3, &moho, &step),
+        );
+        let output = <MohoRecursiveProgram>::execute(&input.into()).unwrap();
 
         let first = expected_attestation(1, 2, &step.predicate);
         let second = expected_attestation(2, 3, &step.predicate);
         assert_eq!(*output.attestation().genesis(), *first.from());
         assert_eq!(*output.attestation().proven(), *second.to());
     }
+
+    #[test]
+    fn test_execute_batch() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let path = [(2, &step), (3, &step), (4, &step)];
+        let input = create_batch_input(&path, Some(1), &moho);
+        let output = <MohoRecursiveProgram>::execute(&input.into()).unwrap();
+
+        let first = expected_attestation(1, 2, &step.predicate);
+        let last = expected_attestation(3, 4, &step.predicate);
+        assert_eq!(*output.attestation().genesis(), *first.from());
+        assert_eq!(*output.attestation().proven(), *last.to());
+        assert_eq!(output.steps(), 3);
+    }
+
+    #[test]
+    fn test_proof_types() {
+        assert!(matches!(
+            <MohoRecursiveProgram as ZkVmProgram>::proof_type(),
+            ProofType::Groth16
+        ));
+        assert!(matches!(
+            <MohoRecursiveProgram<Compressed> as ZkVmProgram>::proof_type(),
+            ProofType::Compressed
+        ));
+        assert!(matches!(
+            <MohoWrapProgram as ZkVmProgram>::proof_type(),
+            ProofType::Groth16
+        ));
+    }
+
+    #[test]
+    fn test_execute_compressed() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let input = create_input(2, 3, Some((1, 2)), &moho, &step);
+        let compressed =
+            MohoRecursiveProgram::<Compressed>::execute(&input.clone().into()).unwrap();
+        let groth16 = <MohoRecursiveProgram>::execute(&input.into()).unwrap();
+        assert_eq!(ssz_encode(&compressed), ssz_encode(&groth16));
+    }
+
+    #[test]
+    fn test_execute_wrap() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let proof = create_recursive_proof(1, 3, &moho, &step);
+        let input = MohoWrapInput::new(moho.predicate.clone(), test_instance(), proof.clone());
+        let output = MohoWrapProgram::execute(&input).unwrap();
+
+        // The wrapping proof commits exactly the claim of the wrapped proof.
+        let expected = MohoRecursiveOutput::new(
+            test_instance(),
+            proof.attestation().clone(),
+            moho.predicate.clone(),
+        );
+        assert_eq!(ssz_encode(&output), ssz_encode(&expected));
+
+        // A wrapped proof attests to that claim under the wrap program's key.
+        let wrap = SchnorrPredicate::new_random();
+        let wrap_proof = |output: &MohoRecursiveOutput| {
+            let signature = wrap.signing_key.sign(&ssz_encode(output)).to_bytes();
+            RecursiveMohoProof::new(output.attestation().clone(), signature.to_vec())
+        };
+        let attestation = verify_wrapped_moho_proof(
+            wrap_proof(&output),
+            &wrap.predicate,
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis(1, &step)),
+        )
+        .unwrap();
+        assert_eq!(attestation, *proof.attestation());
+
+        // The wrap program accepts a recursive proof under any key, but its wrapped proof does
+        // not verify against the trusted inner key.
+        let attacker = SchnorrPredicate::new_random();
+        let forged = create_recursive_proof(1, 3, &attacker, &step);
+        let input = MohoWrapInput::new(attacker.predicate.clone(), test_instance(), forged);
+        let output = MohoWrapProgram::execute(&input).unwrap();
+        let res = verify_wrapped_moho_proof(
+            wrap_proof(&output),
+            &wrap.predicate,
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis(1, &step)),
+        );
+        assert!(matches!(res, Err(MohoError::InvalidRecursiveProof(_))));
+    }
 }
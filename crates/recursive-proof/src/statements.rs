@@ -1,87 +1,192 @@
+use alloc::boxed::Box;
+
 use moho_types::{
-    RecursiveMohoAttestation, RecursiveMohoProof, StepMohoAttestation, StepMohoProof,
+    MohoInstanceId, MohoState, RecursiveMohoAttestation, StepMohoAttestation, StepMohoClaim,
+    StepMohoProof,
 };
 use ssz::ssz_encode;
-use strata_merkle::Sha256NoPrefixHasher;
 use strata_predicate::PredicateKey;
-use tree_hash::{Sha256Hasher, TreeHash};
-use zkaleido::{ZkVmEnv, ZkVmEnvSsz};
 
 use crate::{
-    MohoError, MohoRecursiveInput, MohoRecursiveOutput,
-    errors::{InvalidRecursiveProofError, InvalidStepProofError},
+    MohoAggregateInput, MohoChainInput, MohoError, MohoRecursiveInput, MohoStepProofInput,
+    errors::{GenesisMismatchError, InvalidStepProofError},
+    verifier::verify_recursive_proof,
 };
 
-/// Reads an SSZ-encoded [`MohoRecursiveInput`] from the zkVM, verifies and chains the proof,
-/// and commits the resulting [`MohoRecursiveOutput`] back to the zkVM.
+/// Verifies the input proofs, then combines them into a single [`RecursiveMohoAttestation`].
+///
+/// A [`MohoRecursiveInput::Chain`] input extends an optional recursive proof with a batch of step
+/// proofs, while a [`MohoRecursiveInput::Aggregate`] input merges two recursive proofs covering
+/// adjacent ranges.
 ///
-/// # Panics
+/// All step and recursive proofs are verified against claims bound to the input's
+/// [`MohoInstanceId`]. In either case the resulting attestation must start at the input's
+/// configured genesis: the first step of a base case must start there, and a previous (or
+/// left-hand) recursive proof must have been anchored there.
+pub fn verify_and_chain(input: MohoRecursiveInput) -> Result<RecursiveMohoAttestation, MohoError> {
+    verify_and_chain_with_predecessors(input, &[])
+}
+
+/// Like [`verify_and_chain`], but also accepts a previous recursive proof produced under one of
+/// the `allowed` predecessors of the moho predicate.
 ///
-/// Panics if decoding the input or verifying/chaining the proof fails.
-pub fn process_recursive_moho_proof(zkvm: &impl ZkVmEnv) {
-    let input: MohoRecursiveInput = zkvm.read_ssz();
+/// Recursive proofs are always verified against claims committing to the lineage given in the
+/// input, so a prover cannot alter the lineage of a proof it extends.
+pub fn verify_and_chain_with_predecessors(
+    input: MohoRecursiveInput,
+    allowed: &[PredicateKey],
+) -> Result<RecursiveMohoAttestation, MohoError> {
+    if let MohoRecursiveInput::Chain(input) = &input {
+        check_predecessor(input, allowed)?;
+    }
 
-    let moho_predicate = input.moho_predicate.clone();
-    let attestation = verify_and_chain(input).expect("failed to verify and chain moho proof");
-    let output = MohoRecursiveOutput::new(attestation, moho_predicate);
+    let genesis = *input.genesis();
+    let attestation = match input {
+        MohoRecursiveInput::Chain(input) => verify_chain(input)?,
+        MohoRecursiveInput::Aggregate(input) => verify_aggregate(input)?,
+    };
 
-    zkvm.commit_ssz(&output);
+    if *attestation.genesis() != genesis {
+        return Err(MohoError::GenesisMismatch(Box::new(GenesisMismatchError {
+            expected: genesis,
+            actual: *attestation.genesis(),
+        })));
+    }
+
+    Ok(attestation)
+}
+
+/// Checks that the previous recursive proof of `input`, if any, was produced under either the
+/// moho predicate or one of its `allowed` predecessors.
+fn check_predecessor(input: &MohoChainInput, allowed: &[PredicateKey]) -> Result<(), MohoError> {
+    if input.prev_recursive_proof.is_none() {
+        return Ok(());
+    }
+
+    let prev = input.prev_moho_predicate();
+    if *prev != input.moho_predicate && !allowed.contains(prev) {
+        return Err(MohoError::DisallowedPredecessor(Box::new(prev.clone())));
+    }
+    Ok(())
 }
 
 /// Verifies the step and recursive proofs, then chains them into a single
 /// [`RecursiveMohoAttestation`].
 ///
+/// 1. Verifies the first step proof (see [`verify_batch_step`]).
+/// 2. If a previous recursive proof exists, verifies it under the predicate and lineage it was
+///    produced under and chains the first step onto it — checking that the recursive proof's proven
+///    state matches the step proof's starting state. Otherwise the first step becomes the initial
+///    recursive attestation.
+/// 3. Verifies each remaining step proof in order and chains it onto the accumulated attestation.
+///
+/// Whether the previous proof's predicate is acceptable is checked by
+/// [`verify_and_chain_with_predecessors`].
+fn verify_chain(input: MohoChainInput) -> Result<RecursiveMohoAttestation, MohoError> {
+    let mut steps = input.steps.into_iter();
+
+    // 1: Verify the first step proof.
+    let first = steps.next().ok_or(MohoError::EmptyStepBatch)?;
+    let first_att = verify_batch_step(first, &input.instance)?;
+
+    // 2: Handle previous recursive proof and continuity check.
+    let mut attestation = match input.prev_recursive_proof {
+        // No previous proof: the step becomes the initial recursive attestation.
+        None => {
+            let (from, to) = first_att.into_parts();
+            RecursiveMohoAttestation::new(from, to, 1)
+        }
+
+        // Previous proof exists: verify it under the predicate it was produced under, then
+        // chain.
+        Some(prev_proof) => {
+            let prev_predicate = input
+                .prev_moho_predicate
+                .as_ref()
+                .unwrap_or(&input.moho_predicate);
+            let prev_att = verify_recursive_proof(
+                prev_proof,
+                prev_predicate,
+                &input.instance,
+                &input.prev_lineage,
+            )
+            .map_err(MohoError::InvalidRecursiveProof)?;
+
+            prev_att.chain(first_att)?
+        }
+    };
+
+    // 3: Fold the remaining steps.
+    for step in steps {
+        let step_att = verify_batch_step(step, &input.instance)?;
+        attestation = attestation.chain(step_att)?;
+    }
+
+    Ok(attestation)
+}
+
+/// Verifies a single step of a [`MohoChainInput`].
+///
 /// 1. Verifies that the step predicate key is included in the starting state's Merkle commitment.
 /// 2. Verifies the step proof against the step predicate.
-/// 3. If a previous recursive proof exists, verifies it and chains both attestations — checking
-///    that the recursive proof's proven state matches the step proof's starting state.
-pub fn verify_and_chain(input: MohoRecursiveInput) -> Result<RecursiveMohoAttestation, MohoError> {
+fn verify_batch_step(
+    step: MohoStepProofInput,
+    instance: &MohoInstanceId,
+) -> Result<StepMohoAttestation, MohoError> {
     // 1: Ensure the step proof's predicate key is part of the starting state's Merkle root.
-    let next_predicate_hash =
-        <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(&input.step_predicate).into_inner();
-    let expected_root = input
+    let expected_root = step
         .incremental_step_proof
         .attestation()
         .from()
         .commitment();
-    if !input
-        .step_predicate_merkle_proof
-        .verify_with_root::<Sha256NoPrefixHasher>(expected_root.inner(), &next_predicate_hash)
-    {
+    if !MohoState::verify_next_predicate(
+        expected_root,
+        &step.step_predicate,
+        &step.step_predicate_merkle_proof,
+    ) {
         return Err(MohoError::InvalidMerkleProof);
     }
 
     // 2: Verify the step proof.
-    let step_att = verify_step_proof(input.incremental_step_proof, &input.step_predicate)
-        .map_err(MohoError::InvalidIncrementalProof)?;
-
-    // 3: Handle previous recursive proof and continuity check.
-    match input.prev_recursive_proof {
-        // No previous proof: the step becomes the initial recursive attestation.
-        None => {
-            let (from, to) = step_att.into_parts();
-            Ok(RecursiveMohoAttestation::new(from, to))
-        }
+    verify_step_proof(step.incremental_step_proof, &step.step_predicate, instance)
+        .map_err(MohoError::InvalidIncrementalProof)
+}
 
-        // Previous proof exists: verify it, then chain.
-        Some(prev_proof) => {
-            let prev_att = verify_recursive_proof(prev_proof, &input.moho_predicate)
-                .map_err(MohoError::InvalidRecursiveProof)?;
+/// Verifies two recursive proofs covering adjacent ranges, then merges them into a single
+/// [`RecursiveMohoAttestation`].
+///
+/// Both proofs are verified against the moho predicate, then merged — checking that the left
+/// proof's proven state matches the right proof's genesis.
+fn verify_aggregate(input: MohoAggregateInput) -> Result<RecursiveMohoAttestation, MohoError> {
+    let left = verify_recursive_proof(
+        input.left,
+        &input.moho_predicate,
+        &input.instance,
+        &input.lineage,
+    )
+    .map_err(MohoError::InvalidRecursiveProof)?;
+    let right = verify_recursive_proof(
+        input.right,
+        &input.moho_predicate,
+        &input.instance,
+        &input.lineage,
+    )
+    .map_err(MohoError::InvalidRecursiveProof)?;
 
-            prev_att.chain(step_att).map_err(MohoError::from)
-        }
-    }
+    left.merge(right).map_err(MohoError::from)
 }
 
 /// Verifies a [`StepMohoProof`] against a predicate key.
 ///
-/// Step proofs attest directly to the SSZ-encoded [`StepMohoAttestation`].
+/// Step proofs attest to the SSZ-encoded [`StepMohoClaim`] binding the [`StepMohoAttestation`] to
+/// `instance`.
 /// On success, returns the attestation by consuming the proof.
 fn verify_step_proof(
     proof: StepMohoProof,
     verifier: &PredicateKey,
+    instance: &MohoInstanceId,
 ) -> Result<StepMohoAttestation, Box<InvalidStepProofError>> {
-    let claim = ssz_encode(proof.attestation());
+    let claim = ssz_encode(&StepMohoClaim::new(*instance, proof.attestation().clone()));
     match verifier.verify_claim_witness(&claim, proof.proof()) {
         Ok(()) => Ok(proof.into_attestation()),
         Err(e) => Err(Box::new(InvalidStepProofError {
@@ -91,27 +196,6 @@ fn verify_step_proof(
     }
 }
 
-/// Verifies a [`RecursiveMohoProof`] against a predicate key.
-///
-/// Recursive proofs attest to a [`MohoRecursiveOutput`] which wraps the attestation together
-/// with the predicate key as an additional public value.
-/// On success, returns the attestation by consuming the proof.
-fn verify_recursive_proof(
-    proof: RecursiveMohoProof,
-    verifier: &PredicateKey,
-) -> Result<RecursiveMohoAttestation, Box<InvalidRecursiveProofError>> {
-    let (attestation, proof) = proof.into_parts();
-    let output = MohoRecursiveOutput::new(attestation.clone(), verifier.clone());
-    let claim = ssz_encode(&output);
-    match verifier.verify_claim_witness(&claim, &proof) {
-        Ok(()) => Ok(attestation),
-        Err(e) => Err(Box::new(InvalidRecursiveProofError {
-            attestation,
-            source: e,
-        })),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,12 +207,13 @@ mod tests {
         let step = SchnorrPredicate::new_random();
 
         let expected = expected_attestation(1, 2, &step.predicate);
-        let result = verify_and_chain(create_input(1, 2, None, &moho, &step)).unwrap();
+        let result = verify_chain(create_input(1, 2, None, &moho, &step)).unwrap();
         assert_eq!(*result.genesis(), *expected.from());
         assert_eq!(*result.proven(), *expected.to());
+        assert_eq!(result.steps(), 1);
 
         let expected = expected_attestation(10, 20, &step.predicate);
-        let result = verify_and_chain(create_input(10, 20, None, &moho, &step)).unwrap();
+        let result = verify_chain(create_input(10, 20, None, &moho, &step)).unwrap();
         assert_eq!(*result.genesis(), *expected.from());
         assert_eq!(*result.proven(), *expected.to());
     }
@@ -140,13 +225,14 @@ mod tests {
 
         let from_att = expected_attestation(1, 2, &step.predicate);
         let to_att = expected_attestation(2, 3, &step.predicate);
-        let result = verify_and_chain(create_input(2, 3, Some((1, 2)), &moho, &step)).unwrap();
+        let result = verify_chain(create_input(2, 3, Some((1, 2)), &moho, &step)).unwrap();
         assert_eq!(*result.genesis(), *from_att.from());
         assert_eq!(*result.proven(), *to_att.to());
+        assert_eq!(result.steps(), 2);
 
         let from_att = expected_attestation(1, 3, &step.predicate);
         let to_att = expected_attestation(3, 10, &step.predicate);
-        let result = verify_and_chain(create_input(3, 10, Some((1, 3)), &moho, &step)).unwrap();
+        let result = verify_chain(create_input(3, 10, Some((1, 3)), &moho, &step)).unwrap();
         assert_eq!(*result.genesis(), *from_att.from());
         assert_eq!(*result.proven(), *to_att.to());
     }
@@ -155,7 +241,7 @@ mod tests {
     fn test_verify_and_chain_invalid_chain() {
         let moho = SchnorrPredicate::new_random();
         let step = SchnorrPredicate::new_random();
-        let result = verify_and_chain(create_input(3, 5, Some((1, 2)), &moho, &step));
+        let result = verify_chain(create_input(3, 5, Some((1, 2)), &moho, &step));
         assert!(matches!(result, Err(MohoError::InvalidMohoChain(_))));
     }
 
@@ -164,14 +250,14 @@ mod tests {
         let moho = SchnorrPredicate::new_random();
         let step = SchnorrPredicate::new_random();
         let mut input = create_input(2, 3, None, &moho, &step);
-        input.step_predicate = SchnorrPredicate::new_random().predicate;
+        input.steps[0].step_predicate = SchnorrPredicate::new_random().predicate;
 
-        let result = verify_and_chain(input);
+        let result = verify_chain(input);
         assert!(matches!(result, Err(MohoError::InvalidMerkleProof)));
 
         let expected = expected_attestation(2, 3, &step.predicate);
         let corrected = create_input(2, 3, None, &moho, &step);
-        let result = verify_and_chain(corrected).unwrap();
+        let result = verify_chain(corrected).unwrap();
         assert_eq!(*result.genesis(), *expected.from());
         assert_eq!(*result.proven(), *expected.to());
     }
@@ -185,25 +271,31 @@ mod tests {
         let from_state = create_state(1, step.predicate.clone());
         let to_state = create_state(2, step.predicate.clone());
         let att = step_attestation(1, 2, &from_state, &to_state);
-        let mut bad_signature = sign_attestation(&att, &bad_step_key.signing_key);
+        let mut bad_signature = sign_attestation(&att, &test_instance(), &bad_step_key.signing_key);
         bad_signature[0] ^= 0xFF;
         let step_proof = StepMohoProof::new(att, bad_signature);
 
         // Sanity check: the step proof should not verify under the expected predicate
         assert!(
-            verify_step_proof(step_proof.clone(), &step.predicate).is_err(),
+            verify_step_proof(step_proof.clone(), &step.predicate, &test_instance()).is_err(),
             "corrupted step proof should fail standalone verification"
         );
 
-        let input = MohoRecursiveInput {
+        let input = MohoChainInput {
             moho_predicate: moho.predicate.clone(),
+            instance: test_instance(),
+            genesis: genesis(1, &step),
             prev_recursive_proof: None,
-            incremental_step_proof: step_proof,
-            step_predicate: step.predicate.clone(),
-            step_predicate_merkle_proof: create_predicate_inclusion_proof(&from_state),
+            steps: vec![MohoStepProofInput {
+                step_predicate: step.predicate.clone(),
+                incremental_step_proof: step_proof,
+                step_predicate_merkle_proof: create_predicate_inclusion_proof(&from_state),
+            }],
+            prev_moho_predicate: None,
+            prev_lineage: Vec::new(),
         };
 
-        let result = verify_and_chain(input);
+        let result = verify_chain(input);
         assert!(matches!(result, Err(MohoError::InvalidIncrementalProof(_))));
     }
 
@@ -214,7 +306,7 @@ mod tests {
         let mut input = create_input(2, 3, Some((1, 2)), &moho, &step);
         input.moho_predicate = SchnorrPredicate::new_random().predicate;
 
-        let result = verify_and_chain(input);
+        let result = verify_chain(input);
         assert!(matches!(result, Err(MohoError::InvalidRecursiveProof(_))));
     }
 
@@ -223,6 +315,287 @@ mod tests {
         let moho = SchnorrPredicate::new_random();
         let step = SchnorrPredicate::new_random();
         let input = create_input(5, 5, None, &moho, &step);
-        assert!(verify_and_chain(input).is_ok());
+        assert!(verify_chain(input).is_ok());
+    }
+
+    #[test]
+    fn test_verify_and_chain_dispatches_chain() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let expected = expected_attestation(2, 3, &step.predicate);
+        let input = create_input(2, 3, Some((1, 2)), &moho, &step);
+        let result = verify_and_chain(input.into()).unwrap();
+        assert_eq!(*result.proven(), *expected.to());
+    }
+
+    #[test]
+    fn test_verify_aggregate_success() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let input = MohoAggregateInput::new(
+            moho.predicate.clone(),
+            test_instance(),
+            genesis(1, &step),
+            create_recursive_proof(1, 3, &moho, &step),
+            create_recursive_proof(3, 7, &moho, &step),
+        );
+        let result = verify_and_chain(input.into()).unwrap();
+
+        let first = expected_attestation(1, 3, &step.predicate);
+        let second = expected_attestation(3, 7, &step.predicate);
+        assert_eq!(*result.genesis(), *first.from());
+        assert_eq!(*result.proven(), *second.to());
+        assert_eq!(result.steps(), 6);
+    }
+
+    #[test]
+    fn test_verify_aggregate_invalid_chain() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let input = MohoAggregateInput::new(
+            moho.predicate.clone(),
+            test_instance(),
+            genesis(1, &step),
+            create_recursive_proof(1, 3, &moho, &step),
+            create_recursive_proof(4, 7, &moho, &step),
+        );
+        let result = verify_aggregate(input);
+        assert!(matches!(result, Err(MohoError::InvalidMohoChain(_))));
+    }
+
+    #[test]
+    fn test_verify_aggregate_invalid_recursive_proof() {
+        let moho = SchnorrPredicate::new_random();
+        let other = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let input = MohoAggregateInput::new(
+            moho.predicate.clone(),
+            test_instance(),
+            genesis(1, &step),
+            create_recursive_proof(1, 3, &moho, &step),
+            create_recursive_proof(3, 7, &other, &step),
+        );
+        let result = verify_aggregate(input);
+        assert!(matches!(result, Err(MohoError::InvalidRecursiveProof(_))));
+    }
+
+    #[test]
+    fn test_verify_chain_batch_with_predicate_rotation() {
+        let moho = SchnorrPredicate::new_random();
+        let step_a = SchnorrPredicate::new_random();
+        let step_b = SchnorrPredicate::new_random();
+
+        // The step from 3 to 4 rotates the step predicate from `step_a` to `step_b`.
+        let path = [(2, &step_a), (3, &step_a), (4, &step_b), (5, &step_b)];
+        let input = create_batch_input(&path, Some(1), &moho);
+        let result = verify_chain(input).unwrap();
+
+        let genesis = create_state(1, step_a.predicate.clone());
+        let proven = create_state(5, step_b.predicate.clone());
+        assert_eq!(*result.genesis(), attestation(1, &genesis));
+        assert_eq!(*result.proven(), attestation(5, &proven));
+        assert_eq!(result.steps(), 4);
+    }
+
+    #[test]
+    fn test_verify_chain_batch_base_case() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let path = [(1, &step), (2, &step), (3, &step)];
+        let result = verify_chain(create_batch_input(&path, None, &moho)).unwrap();
+
+        let first = expected_attestation(1, 2, &step.predicate);
+        let last = expected_attestation(2, 3, &step.predicate);
+        assert_eq!(*result.genesis(), *first.from());
+        assert_eq!(*result.proven(), *last.to());
+    }
+
+    #[test]
+    fn test_verify_chain_batch_gap() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let path = [(1, &step), (2, &step), (3, &step), (4, &step)];
+        let mut input = create_batch_input(&path, None, &moho);
+        input.steps.remove(1);
+
+        let result = verify_chain(input);
+        assert!(matches!(result, Err(MohoError::InvalidMohoChain(_))));
+    }
+
+    #[test]
+    fn test_verify_chain_batch_stale_predicate_after_rotation() {
+        let moho = SchnorrPredicate::new_random();
+        let step_a = SchnorrPredicate::new_random();
+        let step_b = SchnorrPredicate::new_random();
+
+        let path = [(1, &step_a), (2, &step_b), (3, &step_b)];
+        let mut input = create_batch_input(&path, None, &moho);
+        // The second step must be verified under the rotated predicate.
+        input.steps[1].step_predicate = step_a.predicate.clone();
+
+        let result = verify_chain(input);
+        assert!(matches!(result, Err(MohoError::InvalidMerkleProof)));
+    }
+
+    #[test]
+    fn test_verify_chain_empty_batch() {
+        let moho = SchnorrPredicate::new_random();
+        let input = MohoChainInput::new(
+            moho.predicate.clone(),
+            test_instance(),
+            genesis(1, &moho),
+            None,
+            vec![],
+        );
+
+        let result = verify_chain(input);
+        assert!(matches!(result, Err(MohoError::EmptyStepBatch)));
+    }
+
+    #[test]
+    fn test_verify_and_chain_base_case_wrong_genesis() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let mut input = create_input(2, 3, None, &moho, &step);
+        input.genesis = genesis(1, &step);
+
+        let result = verify_and_chain(input.into());
+        assert!(matches!(result, Err(MohoError::GenesisMismatch(_))));
+    }
+
+    #[test]
+    fn test_verify_and_chain_prev_proof_wrong_genesis() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        // A valid previous proof that is anchored elsewhere.
+        let mut input = create_input(2, 3, Some((1, 2)), &moho, &step);
+        input.genesis = genesis(0, &step);
+
+        let result = verify_and_chain(input.into());
+        assert!(matches!(result, Err(MohoError::GenesisMismatch(_))));
+    }
+
+    #[test]
+    fn test_verify_aggregate_wrong_genesis() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        // The right-hand proof's own genesis is not accepted as the configured genesis.
+        let input = MohoAggregateInput::new(
+            moho.predicate.clone(),
+            test_instance(),
+            genesis(3, &step),
+            create_recursive_proof(1, 3, &moho, &step),
+            create_recursive_proof(3, 7, &moho, &step),
+        );
+        let result = verify_and_chain(input.into());
+        assert!(matches!(result, Err(MohoError::GenesisMismatch(_))));
+    }
+
+    #[test]
+    fn test_verify_and_chain_rejects_step_proof_of_other_instance() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        // Valid proofs for the test instance are replayed on another deployment sharing the keys.
+        let mut input = create_input(1, 2, None, &moho, &step);
+        input.instance = MohoInstanceId::new([0xEE; 32]);
+
+        let result = verify_chain(input);
+        assert!(matches!(result, Err(MohoError::InvalidIncrementalProof(_))));
+    }
+
+    #[test]
+    fn test_verify_aggregate_rejects_recursive_proof_of_other_instance() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let input = MohoAggregateInput::new(
+            moho.predicate.clone(),
+            MohoInstanceId::new([0xEE; 32]),
+            genesis(1, &step),
+            create_recursive_proof(1, 3, &moho, &step),
+            create_recursive_proof(3, 7, &moho, &step),
+        );
+        let result = verify_aggregate(input);
+        assert!(matches!(result, Err(MohoError::InvalidRecursiveProof(_))));
+    }
+
+    #[test]
+    fn test_verify_and_chain_with_allowed_predecessor() {
+        let old = SchnorrPredicate::new_random();
+        let new = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        // The previous proof was produced under `old`, which `new` replaces.
+        let mut input = create_input(2, 3, Some((1, 2)), &old, &step);
+        input.moho_predicate = new.predicate.clone();
+        let input = input.with_predecessor(old.predicate.clone(), Vec::new());
+        assert_eq!(input.lineage(), [old.predicate.clone()]);
+
+        let allowed = [old.predicate.clone()];
+        let result = verify_and_chain_with_predecessors(input.clone().into(), &allowed).unwrap();
+        let expected = expected_attestation(2, 3, &step.predicate);
+        assert_eq!(*result.proven(), *expected.to());
+        assert_eq!(result.steps(), 2);
+
+        let result = verify_and_chain_with_predecessors(input.clone().into(), &[]);
+        assert!(matches!(result, Err(MohoError::DisallowedPredecessor(_))));
+        let result = verify_and_chain(input.into());
+        assert!(matches!(result, Err(MohoError::DisallowedPredecessor(_))));
+    }
+
+    #[test]
+    fn test_verify_and_chain_keeps_lineage() {
+        let old = SchnorrPredicate::new_random();
+        let new = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        // The previous proof was produced under `new` after a rotation from `old`.
+        let lineage = vec![old.predicate.clone()];
+        let mut input = create_input(2, 3, None, &new, &step);
+        input.genesis = genesis(1, &step);
+        input.prev_recursive_proof = Some(create_recursive_proof_with_lineage(
+            1, 2, &new, &step, &lineage,
+        ));
+        let input = input.with_predecessor(new.predicate.clone(), lineage.clone());
+        assert_eq!(input.lineage(), lineage);
+        assert!(verify_and_chain(input.clone().into()).is_ok());
+
+        // The lineage is part of the previous proof's claim and cannot be dropped.
+        let input = input.with_predecessor(new.predicate.clone(), Vec::new());
+        let result = verify_and_chain(input.into());
+        assert!(matches!(result, Err(MohoError::InvalidRecursiveProof(_))));
+    }
+
+    #[test]
+    fn test_verify_aggregate_with_lineage() {
+        let old = SchnorrPredicate::new_random();
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+
+        let lineage = vec![old.predicate.clone()];
+        let input = MohoAggregateInput::new(
+            moho.predicate.clone(),
+            test_instance(),
+            genesis(1, &step),
+            create_recursive_proof_with_lineage(1, 3, &moho, &step, &lineage),
+            create_recursive_proof_with_lineage(3, 7, &moho, &step, &lineage),
+        );
+        let recursive_input: MohoRecursiveInput =
+            input.clone().with_lineage(lineage.clone()).into();
+        assert_eq!(recursive_input.lineage(), lineage);
+        assert!(verify_and_chain(recursive_input).is_ok());
+
+        let result = verify_aggregate(input);
+        assert!(matches!(result, Err(MohoError::InvalidRecursiveProof(_))));
     }
 }
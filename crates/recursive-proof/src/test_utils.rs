@@ -1,15 +1,14 @@
 //! Test utilities for constructing Moho proofs, states, and transitions.
 use k256::schnorr::{SigningKey, signature::Signer};
 use moho_types::{
-    MohoState, RecursiveMohoAttestation, RecursiveMohoProof, StateRefAttestation, StateReference,
-    StepMohoAttestation, StepMohoProof,
+    MohoInstanceId, MohoState, RecursiveMohoAttestation, RecursiveMohoProof, SpecCommitment,
+    StateRefAttestation, StateReference, StepMohoAttestation, StepMohoClaim, StepMohoProof,
 };
 use ssz::ssz_encode;
-use strata_merkle::{BinaryMerkleTree, MerkleProofB32, Sha256NoPrefixHasher};
+use strata_merkle::MerkleProofB32;
 use strata_predicate::{PredicateKey, PredicateTypeId};
-use tree_hash::{Sha256Hasher as TreeSha256Hasher, TreeHash};
 
-use crate::{MohoRecursiveOutput, io::MohoRecursiveInput};
+use crate::{MohoChainInput, MohoRecursiveOutput, MohoStepProofInput};
 
 /// A Schnorr key pair bundled with a [`PredicateKey`] for convenient test setup.
 #[derive(Clone)]
@@ -41,7 +40,12 @@ impl SchnorrPredicate {
 pub fn create_state(id: u8, predicate: PredicateKey) -> MohoState {
     let inner_state = moho_types::InnerStateCommitment::from([id; 32]);
     let export_state = moho_types::ExportState::new(vec![]).unwrap();
-    MohoState::new(inner_state, predicate, export_state)
+    MohoState::new(
+        inner_state,
+        predicate,
+        export_state,
+        SpecCommitment::default(),
+    )
 }
 
 /// Creates a [`StateRefAttestation`] for the given `id` and `state`.
@@ -53,18 +57,7 @@ pub fn attestation(id: u8, state: &MohoState) -> StateRefAttestation {
 
 /// Creates a Merkle inclusion proof for the predicate within the given state.
 pub fn create_predicate_inclusion_proof(state: &MohoState) -> MerkleProofB32 {
-    let leaves = vec![
-        <_ as TreeHash>::tree_hash_root::<TreeSha256Hasher>(&state.inner_state).into_inner(),
-        <_ as TreeHash>::tree_hash_root::<TreeSha256Hasher>(&state.next_predicate).into_inner(),
-        <_ as TreeHash>::tree_hash_root::<TreeSha256Hasher>(&state.export_state).into_inner(),
-        [0u8; 32],
-    ];
-
-    let generic_proof = BinaryMerkleTree::from_leaves::<Sha256NoPrefixHasher>(leaves)
-        .expect("valid tree")
-        .gen_proof(1)
-        .expect("proof exists");
-    MerkleProofB32::from_generic(&generic_proof)
+    state.prove_next_predicate()
 }
 
 /// Creates a [`StepMohoAttestation`] between two states identified by `from`/`to` IDs.
@@ -77,9 +70,20 @@ pub fn step_attestation(
     StepMohoAttestation::new(attestation(from, from_state), attestation(to, to_state))
 }
 
-/// Signs a [`StepMohoAttestation`] with the given signing key.
-pub fn sign_attestation(att: &StepMohoAttestation, signing_key: &SigningKey) -> Vec<u8> {
-    signing_key.sign(&ssz_encode(att)).to_bytes().to_vec()
+/// Returns the Moho instance all test proofs are bound to.
+pub fn test_instance() -> MohoInstanceId {
+    MohoInstanceId::new([0x4d; 32])
+}
+
+/// Signs the [`StepMohoClaim`] binding a [`StepMohoAttestation`] to `instance` with the given
+/// signing key.
+pub fn sign_attestation(
+    att: &StepMohoAttestation,
+    instance: &MohoInstanceId,
+    signing_key: &SigningKey,
+) -> Vec<u8> {
+    let claim = StepMohoClaim::new(*instance, att.clone());
+    signing_key.sign(&ssz_encode(&claim)).to_bytes().to_vec()
 }
 
 /// Creates a [`StepMohoProof`] and its corresponding Merkle inclusion proof.
@@ -91,53 +95,132 @@ pub fn step_proof_with_merkle(
     signing_key: &SigningKey,
 ) -> (StepMohoProof, MerkleProofB32) {
     let att = step_attestation(from, to, from_state, to_state);
-    let signature = sign_attestation(&att, signing_key);
+    let signature = sign_attestation(&att, &test_instance(), signing_key);
     let proof = StepMohoProof::new(att, signature);
     let merkle_proof = create_predicate_inclusion_proof(from_state);
     (proof, merkle_proof)
 }
 
-/// Creates a complete [`MohoRecursiveInput`] for testing.
+/// Creates a [`RecursiveMohoProof`] of the test instance transitioning from `from` to `to` in
+/// `to - from` steps, signed with the `moho` predicate's signing key.
+pub fn create_recursive_proof(
+    from: u8,
+    to: u8,
+    moho: &SchnorrPredicate,
+    step: &SchnorrPredicate,
+) -> RecursiveMohoProof {
+    create_recursive_proof_with_lineage(from, to, moho, step, &[])
+}
+
+/// Like [`create_recursive_proof`], but the proof commits to the given `lineage`.
+pub fn create_recursive_proof_with_lineage(
+    from: u8,
+    to: u8,
+    moho: &SchnorrPredicate,
+    step: &SchnorrPredicate,
+    lineage: &[PredicateKey],
+) -> RecursiveMohoProof {
+    let from_state = create_state(from, step.predicate.clone());
+    let to_state = create_state(to, step.predicate.clone());
+    let rec_att = RecursiveMohoAttestation::new(
+        attestation(from, &from_state),
+        attestation(to, &to_state),
+        (to - from).into(),
+    );
+    let output = MohoRecursiveOutput::new(test_instance(), rec_att.clone(), moho.predicate.clone())
+        .with_lineage(lineage.to_vec());
+    let signature = moho
+        .signing_key
+        .sign(&ssz_encode(&output))
+        .to_bytes()
+        .to_vec();
+    RecursiveMohoProof::new(rec_att, signature)
+}
+
+/// Creates a complete [`MohoChainInput`] for testing.
 ///
 /// If `prev` is `Some((f, t))`, a previous recursive proof transitioning from `f` to `t`
-/// is included, signed with the `moho` predicate's signing key.
+/// is included, signed with the `moho` predicate's signing key. The genesis is the starting state
+/// of the previous proof if any, or of the step otherwise.
 pub fn create_input(
     from: u8,
     to: u8,
     prev: Option<(u8, u8)>,
     moho: &SchnorrPredicate,
     step: &SchnorrPredicate,
-) -> MohoRecursiveInput {
+) -> MohoChainInput {
     let from_state = create_state(from, step.predicate.clone());
     let to_state = create_state(to, step.predicate.clone());
     let (step_proof, step_predicate_merkle_proof) =
         step_proof_with_merkle(from, to, &from_state, &to_state, &step.signing_key);
 
-    let prev_recursive_proof = prev.map(|(f, t)| {
-        let prev_from_state = create_state(f, step.predicate.clone());
-        let prev_to_state = create_state(t, step.predicate.clone());
-        let rec_att = RecursiveMohoAttestation::new(
-            attestation(f, &prev_from_state),
-            attestation(t, &prev_to_state),
-        );
-        let output = MohoRecursiveOutput::new(rec_att.clone(), moho.predicate.clone());
-        let signature = moho
-            .signing_key
-            .sign(&ssz_encode(&output))
-            .to_bytes()
-            .to_vec();
-        RecursiveMohoProof::new(rec_att, signature)
-    });
-
-    MohoRecursiveInput {
+    let prev_recursive_proof = prev.map(|(f, t)| create_recursive_proof(f, t, moho, step));
+    let genesis_id = prev.map_or(from, |(f, _)| f);
+
+    MohoChainInput {
+        moho_predicate: moho.predicate.clone(),
+        instance: test_instance(),
+        genesis: genesis(genesis_id, step),
+        prev_recursive_proof,
+        steps: vec![MohoStepProofInput {
+            step_predicate: step.predicate.clone(),
+            incremental_step_proof: step_proof,
+            step_predicate_merkle_proof,
+        }],
+        prev_moho_predicate: None,
+        prev_lineage: Vec::new(),
+    }
+}
+
+/// Creates a [`MohoChainInput`] batching the steps along `path`.
+///
+/// Each `(id, predicate)` in `path` is a state whose next predicate is `predicate`; the step
+/// leaving it is signed with that predicate's signing key. If `prev_from` is `Some(f)`, a
+/// previous recursive proof transitioning from `f` to the first state of `path` is included and
+/// `f` is the genesis; otherwise the first state of `path` is.
+pub fn create_batch_input(
+    path: &[(u8, &SchnorrPredicate)],
+    prev_from: Option<u8>,
+    moho: &SchnorrPredicate,
+) -> MohoChainInput {
+    let steps = path
+        .windows(2)
+        .map(|pair| {
+            let [(from, from_key), (to, to_key)] = pair else {
+                unreachable!("windows of size 2")
+            };
+            let from_state = create_state(*from, from_key.predicate.clone());
+            let to_state = create_state(*to, to_key.predicate.clone());
+            let (proof, merkle_proof) =
+                step_proof_with_merkle(*from, *to, &from_state, &to_state, &from_key.signing_key);
+            MohoStepProofInput {
+                step_predicate: from_key.predicate.clone(),
+                incremental_step_proof: proof,
+                step_predicate_merkle_proof: merkle_proof,
+            }
+        })
+        .collect();
+
+    let (first, first_key) = path[0];
+    let prev_recursive_proof = prev_from.map(|f| create_recursive_proof(f, first, moho, first_key));
+
+    MohoChainInput {
         moho_predicate: moho.predicate.clone(),
+        instance: test_instance(),
+        genesis: genesis(prev_from.unwrap_or(first), first_key),
         prev_recursive_proof,
-        incremental_step_proof: step_proof,
-        step_predicate: step.predicate.clone(),
-        step_predicate_merkle_proof,
+        steps,
+        prev_moho_predicate: None,
+        prev_lineage: Vec::new(),
     }
 }
 
+/// Creates the [`StateRefAttestation`] of state `id` whose next predicate is `step`'s, for use as
+/// a configured genesis.
+pub fn genesis(id: u8, step: &SchnorrPredicate) -> StateRefAttestation {
+    attestation(id, &create_state(id, step.predicate.clone()))
+}
+
 /// Creates the expected [`StepMohoAttestation`] for a given `from`/`to` pair and predicate.
 pub fn expected_attestation(from: u8, to: u8, predicate: &PredicateKey) -> StepMohoAttestation {
     let from_state = create_state(from, predicate.clone());
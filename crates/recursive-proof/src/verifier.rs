@@ -0,0 +1,334 @@
+//! Verification of recursive Moho proofs by light clients.
+//!
+//! A light client trusts a moho predicate key, a Moho instance and its genesis. Given a
+//! [`RecursiveMohoProof`], [`verify_recursive_moho_proof`] checks it against these and returns
+//! the verified [`RecursiveMohoAttestation`]. Clients that are also handed the proven
+//! [`MohoState`] — e.g. to read its export state — can then check it against the attestation
+//! with [`verify_proven_state`]. Proofs wrapped into Groth16 proofs by the
+//! [`MohoWrapProgram`](crate::MohoWrapProgram) are checked with [`verify_wrapped_moho_proof`]
+//! instead.
+//!
+//! Consumer zk programs, e.g. bridge proofs, do the same inside their own guest with
+//! [`verify_export_state`], which returns the verified [`ExportState`] against which export
+//! entries can then be checked. [`read_verified_export_state`](crate::read_verified_export_state)
+//! reads its arguments from the zkVM first.
+
+use alloc::boxed::Box;
+
+use moho_types::{
+    ExportState, MohoInstanceId, MohoState, RecursiveMohoAttestation, RecursiveMohoProof,
+    StateRefAttestation,
+};
+use ssz::ssz_encode;
+use strata_predicate::PredicateKey;
+
+use crate::{
+    MohoError, MohoRecursiveOutput,
+    errors::{GenesisMismatchError, InvalidRecursiveProofError, ProvenStateMismatchError},
+};
+
+/// Verifies a [`RecursiveMohoProof`] of `instance` against the moho predicate key.
+///
+/// The proof must attest to the [`MohoRecursiveOutput`] binding its attestation to the recursive
+/// domain of `instance`, to `moho_predicate` and to the `lineage` of predecessors the proof
+/// reports (see [`MohoRecursiveOutput::lineage`]). If `expected_genesis` is given, the attestation
+/// must additionally start at it.
+///
+/// Verifiers should always pass the genesis they trust. The recursive program only checks that
+/// the chain starts at the genesis given in its [`MohoChainInput`](crate::MohoChainInput), which
+/// the prover chooses, so without `expected_genesis` a proof only attests to a chain that is
+/// consistent with some genesis, not with the instance's actual one.
+///
+/// # Errors
+///
+/// Returns [`MohoError::InvalidRecursiveProof`] if the proof does not verify, or
+/// [`MohoError::GenesisMismatch`] if it does not start at `expected_genesis`.
+pub fn verify_recursive_moho_proof(
+    proof: RecursiveMohoProof,
+    moho_predicate: &PredicateKey,
+    instance: &MohoInstanceId,
+    lineage: &[PredicateKey],
+    expected_genesis: Option<&StateRefAttestation>,
+) -> Result<RecursiveMohoAttestation, MohoError> {
+    let attestation = verify_recursive_proof(proof, moho_predicate, instance, lineage)
+        .map_err(MohoError::InvalidRecursiveProof)?;
+    check_genesis(&attestation, expected_genesis)?;
+    Ok(attestation)
+}
+
+/// Verifies a [`RecursiveMohoProof`] of `instance` wrapped by the
+/// [`MohoWrapProgram`](crate::MohoWrapProgram).
+///
+/// The proof is verified against `wrap_key`, the predicate key of the wrap program, but must
+/// attest to the same [`MohoRecursiveOutput`] as a recursive proof under `trusted_inner_key`
+/// would. The wrap program verifies the inner proof against whatever key it is given, so binding
+/// the claim to the trusted inner key is what rejects wraps of proofs produced by other recursive
+/// programs. `lineage` and `expected_genesis` are checked as in [`verify_recursive_moho_proof`].
+///
+/// # Errors
+///
+/// Returns [`MohoError::InvalidRecursiveProof`] if the proof does not verify, or
+/// [`MohoError::GenesisMismatch`] if it does not start at `expected_genesis`.
+pub fn verify_wrapped_moho_proof(
+    proof: RecursiveMohoProof,
+    wrap_key: &PredicateKey,
+    trusted_inner_key: &PredicateKey,
+    instance: &MohoInstanceId,
+    lineage: &[PredicateKey],
+    expected_genesis: Option<&StateRefAttestation>,
+) -> Result<RecursiveMohoAttestation, MohoError> {
+    let attestation = verify_claim(proof, wrap_key, trusted_inner_key, instance, lineage)
+        .map_err(MohoError::InvalidRecursiveProof)?;
+    check_genesis(&attestation, expected_genesis)?;
+    Ok(attestation)
+}
+
+/// Checks that `attestation` starts at `expected_genesis`, if given.
+fn check_genesis(
+    attestation: &RecursiveMohoAttestation,
+    expected_genesis: Option<&StateRefAttestation>,
+) -> Result<(), MohoError> {
+    if let Some(expected) = expected_genesis
+        && attestation.genesis() != expected
+    {
+        return Err(MohoError::GenesisMismatch(Box::new(GenesisMismatchError {
+            expected: *expected,
+            actual: *attestation.genesis(),
+        })));
+    }
+    Ok(())
+}
+
+/// Verifies that `state` is the proven state of a verified `attestation`.
+///
+/// # Errors
+///
+/// Returns [`MohoError::ProvenStateMismatch`] if the commitment of `state` does not match the
+/// proven commitment.
+pub fn verify_proven_state(
+    attestation: &RecursiveMohoAttestation,
+    state: &MohoState,
+) -> Result<(), MohoError> {
+    let expected = *attestation.proven().commitment();
+    let actual = state.compute_commitment();
+    if actual != expected {
+        return Err(MohoError::ProvenStateMismatch(Box::new(
+            ProvenStateMismatchError { expected, actual },
+        )));
+    }
+    Ok(())
+}
+
+/// Verifies `proof` with [`verify_recursive_moho_proof`] and `state` against its proven state
+/// with [`verify_proven_state`], then returns the export state of `state`.
+///
+/// # Errors
+///
+/// Returns a [`MohoError`] if either verification fails.
+pub fn verify_export_state(
+    proof: RecursiveMohoProof,
+    state: MohoState,
+    moho_predicate: &PredicateKey,
+    instance: &MohoInstanceId,
+    lineage: &[PredicateKey],
+    expected_genesis: Option<&StateRefAttestation>,
+) -> Result<ExportState, MohoError> {
+    let attestation =
+        verify_recursive_moho_proof(proof, moho_predicate, instance, lineage, expected_genesis)?;
+    verify_proven_state(&attestation, &state)?;
+    Ok(state.into_export_state())
+}
+
+/// Verifies a [`RecursiveMohoProof`] against a predicate key.
+///
+/// Recursive proofs attest to a [`MohoRecursiveOutput`] which wraps the attestation together
+/// with the recursive domain of `instance`, the predicate key and the `lineage` of its
+/// predecessors as additional public values.
+/// On success, returns the attestation by consuming the proof.
+pub(crate) fn verify_recursive_proof(
+    proof: RecursiveMohoProof,
+    verifier: &PredicateKey,
+    instance: &MohoInstanceId,
+    lineage: &[PredicateKey],
+) -> Result<RecursiveMohoAttestation, Box<InvalidRecursiveProofError>> {
+    verify_claim(proof, verifier, verifier, instance, lineage)
+}
+
+/// Verifies a [`RecursiveMohoProof`] against `verifier`, checking that it attests to the
+/// [`MohoRecursiveOutput`] produced under `moho_predicate`.
+///
+/// The two keys differ for wrapped proofs, which are produced by a different program than the
+/// recursive program whose output they commit.
+fn verify_claim(
+    proof: RecursiveMohoProof,
+    verifier: &PredicateKey,
+    moho_predicate: &PredicateKey,
+    instance: &MohoInstanceId,
+    lineage: &[PredicateKey],
+) -> Result<RecursiveMohoAttestation, Box<InvalidRecursiveProofError>> {
+    let (attestation, proof) = proof.into_parts();
+    let output = MohoRecursiveOutput::new(*instance, attestation.clone(), moho_predicate.clone())
+        .with_lineage(lineage.to_vec());
+    let claim = ssz_encode(&output);
+    match verifier.verify_claim_witness(&claim, &proof) {
+        Ok(()) => Ok(attestation),
+        Err(e) => Err(Box::new(InvalidRecursiveProofError {
+            attestation,
+            source: e,
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_verify_recursive_moho_proof() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+        let proof = create_recursive_proof(1, 4, &moho, &step);
+
+        let attestation = verify_recursive_moho_proof(
+            proof.clone(),
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            None,
+        )
+        .unwrap();
+        assert_eq!(attestation, *proof.attestation());
+        assert_eq!(attestation.steps(), 3);
+
+        let attestation = verify_recursive_moho_proof(
+            proof,
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis(1, &step)),
+        )
+        .unwrap();
+        assert_eq!(*attestation.genesis(), genesis(1, &step));
+    }
+
+    #[test]
+    fn test_verify_recursive_moho_proof_rejects_mismatches() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+        let proof = create_recursive_proof(1, 4, &moho, &step);
+
+        let wrong_predicate = SchnorrPredicate::new_random().predicate;
+        let res = verify_recursive_moho_proof(
+            proof.clone(),
+            &wrong_predicate,
+            &test_instance(),
+            &[],
+            None,
+        );
+        assert!(matches!(res, Err(MohoError::InvalidRecursiveProof(_))));
+
+        let other_instance = MohoInstanceId::new([0x99; 32]);
+        let res =
+            verify_recursive_moho_proof(proof.clone(), &moho.predicate, &other_instance, &[], None);
+        assert!(matches!(res, Err(MohoError::InvalidRecursiveProof(_))));
+
+        let res = verify_recursive_moho_proof(
+            proof,
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis(2, &step)),
+        );
+        assert!(matches!(res, Err(MohoError::GenesisMismatch(_))));
+
+        // A proof of a chain the prover anchored at a genesis of their choosing is valid on its
+        // own, and only rejected against the genesis the verifier trusts.
+        let forged = create_recursive_proof(2, 4, &moho, &step);
+        let res = verify_recursive_moho_proof(
+            forged.clone(),
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            None,
+        );
+        assert_eq!(*res.unwrap().genesis(), genesis(2, &step));
+        match verify_recursive_moho_proof(
+            forged,
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis(1, &step)),
+        ) {
+            Err(MohoError::GenesisMismatch(err)) => {
+                assert_eq!(err.expected, genesis(1, &step));
+                assert_eq!(err.actual, genesis(2, &step));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_verify_proven_state() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+        let proof = create_recursive_proof(1, 4, &moho, &step);
+        let attestation =
+            verify_recursive_moho_proof(proof, &moho.predicate, &test_instance(), &[], None)
+                .unwrap();
+
+        let proven = create_state(4, step.predicate.clone());
+        assert!(verify_proven_state(&attestation, &proven).is_ok());
+
+        let other = create_state(3, step.predicate.clone());
+        match verify_proven_state(&attestation, &other) {
+            Err(MohoError::ProvenStateMismatch(err)) => {
+                assert_eq!(err.expected, proven.compute_commitment());
+                assert_eq!(err.actual, other.compute_commitment());
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_verify_export_state() {
+        let moho = SchnorrPredicate::new_random();
+        let step = SchnorrPredicate::new_random();
+        let proof = create_recursive_proof(1, 4, &moho, &step);
+        let genesis = genesis(1, &step);
+
+        let proven = create_state(4, step.predicate.clone());
+        let export_state = verify_export_state(
+            proof.clone(),
+            proven.clone(),
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis),
+        )
+        .unwrap();
+        assert_eq!(export_state, *proven.export_state());
+
+        let other = create_state(3, step.predicate.clone());
+        let res = verify_export_state(
+            proof.clone(),
+            other,
+            &moho.predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis),
+        );
+        assert!(matches!(res, Err(MohoError::ProvenStateMismatch(_))));
+
+        let wrong_predicate = SchnorrPredicate::new_random().predicate;
+        let res = verify_export_state(
+            proof,
+            proven,
+            &wrong_predicate,
+            &test_instance(),
+            &[],
+            Some(&genesis),
+        );
+        assert!(matches!(res, Err(MohoError::InvalidRecursiveProof(_))));
+    }
+}
@@ -0,0 +1,306 @@
+//! Host-side driver running a [`MohoProgram`] over a stream of step inputs.
+//!
+//! [`MohoChainDriver`] owns the genesis state of a Moho chain and applies step inputs one by one,
+//! keeping the Moho state, inner state and [`StepMohoAttestation`] produced by every step so that
+//! hosts can build the next [`RuntimeInput`], serve historical states and roll back reorged steps.
+
+use alloc::{vec, vec::Vec};
+use core::{fmt, marker::PhantomData};
+
+use moho_runtime_interface::{MohoProgram, SpecSchedule};
+use moho_types::{ExportState, MohoState, StepMohoAttestation};
+use ssz::{Decode, DecodeError, Encode};
+
+use crate::{RuntimeError, RuntimeInput, try_compute_moho_step};
+
+/// A state of the chain tracked by a [`MohoChainDriver`].
+#[derive(Clone, Debug)]
+struct DriverState {
+    /// The Moho state.
+    moho_state: MohoState,
+
+    /// SSZ-encoded inner state (`P::State`) committed to by `moho_state`.
+    inner_state: Vec<u8>,
+}
+
+/// Runs a [`MohoProgram`] over a stream of step inputs, starting from a genesis state.
+///
+/// Step `0` is the genesis state; applying the `n`-th input produces step `n`. The driver keeps
+/// every step so that any of them can be inspected, or the chain rewound to it.
+pub struct MohoChainDriver<P: MohoProgram> {
+    /// The schedule selecting the spec each transition runs under.
+    schedule: SpecSchedule<P::Spec>,
+
+    /// The state at every step, starting with genesis.
+    states: Vec<DriverState>,
+
+    /// The attestation produced by every step after genesis; `attestations[n - 1]` leads to step
+    /// `n`.
+    attestations: Vec<StepMohoAttestation>,
+
+    _program: PhantomData<P>,
+}
+
+impl<P: MohoProgram> fmt::Debug for MohoChainDriver<P>
+where
+    P::Spec: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MohoChainDriver")
+            .field("schedule", &self.schedule)
+            .field("steps", &self.steps())
+            .field("moho_state", self.moho_state())
+            .finish()
+    }
+}
+
+impl<P: MohoProgram> MohoChainDriver<P> {
+    /// Creates a driver starting from the given genesis states, running transitions under the
+    /// specs selected from `schedule`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RuntimeError::InnerStateCommitmentMismatch`] if `genesis_moho_state` does not
+    /// commit to `genesis_inner_state`.
+    pub fn new(
+        schedule: SpecSchedule<P::Spec>,
+        genesis_moho_state: MohoState,
+        genesis_inner_state: &P::State,
+    ) -> Result<Self, RuntimeError<P::Error>> {
+        let computed = P::compute_state_commitment(genesis_inner_state);
+        let expected = genesis_moho_state.inner_state();
+        if computed != expected {
+            return Err(RuntimeError::InnerStateCommitmentMismatch { expected, computed });
+        }
+
+        Ok(Self {
+            schedule,
+            states: vec![DriverState {
+                moho_state: genesis_moho_state,
+                inner_state: genesis_inner_state.as_ssz_bytes(),
+            }],
+            attestations: Vec::new(),
+            _program: PhantomData,
+        })
+    }
+
+    /// Returns the schedule selecting the spec each transition runs under.
+    pub fn schedule(&self) -> &SpecSchedule<P::Spec> {
+        &self.schedule
+    }
+
+    /// Returns the number of steps applied since genesis.
+    pub fn steps(&self) -> usize {
+        self.attestations.len()
+    }
+
+    /// Builds the [`RuntimeInput`] applying `input` to the current state.
+    ///
+    /// After the first step, the input is required to extend the reference the last step
+    /// produced.
+    pub fn runtime_input(&self, input: &P::StepInput) -> RuntimeInput {
+        let current = self.current();
+        let runtime_input = RuntimeInput::new(
+            current.moho_state.clone(),
+            current.inner_state.clone(),
+            input.as_ssz_bytes(),
+        );
+        match self.attestations.last() {
+            Some(last) => runtime_input.with_expected_prev_reference(*last.to().reference()),
+            None => runtime_input,
+        }
+    }
+
+    /// Applies a step input to the current state, returning the attestation it produced.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RuntimeError`] if the runtime rejects the step, in which case the driver is
+    /// left unchanged.
+    pub fn apply(
+        &mut self,
+        input: &P::StepInput,
+    ) -> Result<&StepMohoAttestation, RuntimeError<P::Error>> {
+        let (attestation, moho_state, inner_state) =
+            try_compute_moho_step::<P>(self.runtime_input(input), &self.schedule)?.into_parts();
+
+        self.states.push(DriverState {
+            moho_state,
+            inner_state,
+        });
+        self.attestations.push(attestation);
+        Ok(self
+            .attestations
+            .last()
+            .expect("attestation was just pushed"))
+    }
+
+    /// Rewinds the chain to `step`, discarding all later steps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is greater than [`steps`](Self::steps).
+    pub fn rewind(&mut self, step: usize) {
+        assert!(
+            step <= self.steps(),
+            "driver: cannot rewind to step {step}, only {} steps applied",
+            self.steps()
+        );
+        self.states.truncate(step + 1);
+        self.attestations.truncate(step);
+    }
+
+    /// Returns the current Moho state.
+    pub fn moho_state(&self) -> &MohoState {
+        &self.current().moho_state
+    }
+
+    /// Returns the current SSZ-encoded inner state.
+    pub fn inner_state_bytes(&self) -> &[u8] {
+        &self.current().inner_state
+    }
+
+    /// Returns the current inner state.
+    pub fn inner_state(&self) -> Result<P::State, DecodeError> {
+        P::State::from_ssz_bytes(self.inner_state_bytes())
+    }
+
+    /// Returns the current export state.
+    pub fn export_state(&self) -> &ExportState {
+        self.moho_state().export_state()
+    }
+
+    /// Returns the Moho state at `step`, or `None` if it has not been applied.
+    pub fn moho_state_at(&self, step: usize) -> Option<&MohoState> {
+        self.states.get(step).map(|s| &s.moho_state)
+    }
+
+    /// Returns the SSZ-encoded inner state at `step`, or `None` if it has not been applied.
+    pub fn inner_state_bytes_at(&self, step: usize) -> Option<&[u8]> {
+        self.states.get(step).map(|s| s.inner_state.as_slice())
+    }
+
+    /// Returns the attestation that produced `step`, or `None` for genesis or if it has not been
+    /// applied.
+    pub fn attestation_at(&self, step: usize) -> Option<&StepMohoAttestation> {
+        step.checked_sub(1).and_then(|i| self.attestations.get(i))
+    }
+
+    /// Returns the attestations of all applied steps, in order.
+    pub fn attestations(&self) -> &[StepMohoAttestation] {
+        &self.attestations
+    }
+
+    fn current(&self) -> &DriverState {
+        self.states.last().expect("driver always holds genesis")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_moho_attestation, test_program::*};
+
+    fn driver() -> MohoChainDriver<CounterProgram> {
+        MohoChainDriver::new(SCHEDULE, counter_moho_state(0), &0).unwrap()
+    }
+
+    #[test]
+    fn test_apply_matches_runtime() {
+        let mut driver = driver();
+
+        let input = CounterInput::new(0, 4);
+        let expected =
+            compute_moho_attestation::<CounterProgram>(driver.runtime_input(&input), &SCHEDULE);
+        let att = driver.apply(&input).unwrap().clone();
+
+        assert_eq!(att, expected);
+        assert_eq!(driver.steps(), 1);
+        assert_eq!(driver.inner_state().unwrap(), 4);
+        assert_eq!(
+            *att.to().commitment(),
+            driver.moho_state().compute_commitment()
+        );
+        assert_eq!(
+            driver.export_state().containers()[0].container_id(),
+            COUNTER_CONTAINER_ID
+        );
+    }
+
+    #[test]
+    fn test_attestations_are_continuous() {
+        let mut driver = driver();
+        for (i, delta) in [1, 2, 3, 4].into_iter().enumerate() {
+            driver.apply(&CounterInput::new(i as u8, delta)).unwrap();
+        }
+
+        assert_eq!(driver.inner_state().unwrap(), 10);
+        for pair in driver.attestations().windows(2) {
+            assert_eq!(pair[0].to(), pair[1].from());
+        }
+        for step in 1..=driver.steps() {
+            let att = driver.attestation_at(step).unwrap();
+            let state = driver.moho_state_at(step).unwrap();
+            assert_eq!(*att.to().commitment(), state.compute_commitment());
+        }
+    }
+
+    #[test]
+    fn test_rejected_step_leaves_driver_unchanged() {
+        let mut driver = driver();
+        driver.apply(&CounterInput::new(0, 1)).unwrap();
+
+        let res = driver.apply(&CounterInput::new(1, 0));
+        assert!(matches!(res, Err(RuntimeError::ProgramRejected(_))));
+        assert_eq!(driver.steps(), 1);
+        assert_eq!(driver.inner_state().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_apply_rejects_discontinuous_input() {
+        let mut driver = driver();
+        driver.apply(&CounterInput::new(0, 1)).unwrap();
+
+        let res = driver.apply(&CounterInput::new(5, 1));
+        assert!(matches!(res, Err(RuntimeError::ReferenceMismatch { .. })));
+        assert_eq!(driver.steps(), 1);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut driver = driver();
+        for (i, delta) in [1, 2, 3].into_iter().enumerate() {
+            driver.apply(&CounterInput::new(i as u8, delta)).unwrap();
+        }
+        let state_at_1 = driver.moho_state_at(1).unwrap().clone();
+
+        driver.rewind(1);
+        assert_eq!(driver.steps(), 1);
+        assert_eq!(*driver.moho_state(), state_at_1);
+        assert_eq!(driver.inner_state().unwrap(), 1);
+        assert!(driver.attestation_at(2).is_none());
+
+        // Apply a different step on top of the rewound state.
+        driver.apply(&CounterInput::new(1, 10)).unwrap();
+        assert_eq!(driver.inner_state().unwrap(), 11);
+
+        driver.rewind(0);
+        assert_eq!(driver.steps(), 0);
+        assert_eq!(*driver.moho_state(), counter_moho_state(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot rewind")]
+    fn test_rewind_past_tip_panics() {
+        driver().rewind(1);
+    }
+
+    #[test]
+    fn test_new_rejects_mismatched_genesis() {
+        let res = MohoChainDriver::<CounterProgram>::new(SCHEDULE, counter_moho_state(0), &1);
+        assert!(matches!(
+            res,
+            Err(RuntimeError::InnerStateCommitmentMismatch { .. })
+        ));
+    }
+}
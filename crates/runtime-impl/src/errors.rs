@@ -0,0 +1,58 @@
+//! Error types for the Moho runtime.
+
+use moho_types::{ExportStateError, InnerStateCommitment, SpecCommitment, StateReference};
+use ssz::DecodeError;
+use thiserror::Error;
+
+/// Errors that can occur while computing a single Moho state transition.
+///
+/// Generic over the [`MohoProgram::Error`](moho_runtime_interface::MohoProgram::Error) so that
+/// program rejections are surfaced without losing their concrete type.
+#[derive(Debug, Error)]
+pub enum RuntimeError<E> {
+    /// The SSZ-encoded inner pre-state could not be decoded.
+    #[error("failed to decode inner pre-state: {0:?}")]
+    InnerStateDecode(DecodeError),
+
+    /// The SSZ-encoded step input could not be decoded.
+    #[error("failed to decode step input: {0:?}")]
+    StepInputDecode(DecodeError),
+
+    /// The commitment of the provided inner pre-state does not match the one stored in the Moho
+    /// pre-state.
+    #[error(
+        "inner pre-state commitment {computed} does not match moho pre-state commitment {expected}"
+    )]
+    InnerStateCommitmentMismatch {
+        /// The commitment stored in the Moho pre-state.
+        expected: InnerStateCommitment,
+        /// The commitment computed from the provided inner pre-state.
+        computed: InnerStateCommitment,
+    },
+
+    /// An export operation returned by the program could not be applied to the export state.
+    #[error("failed to apply export operation: {0}")]
+    InvalidExportOp(ExportStateError),
+
+    /// The step input's previous reference does not match the expected one carried by the
+    /// runtime input.
+    #[error("step input extends reference {actual}, but expected {expected}")]
+    ReferenceMismatch {
+        /// The reference the previous step produced.
+        expected: StateReference,
+        /// The previous reference extracted from the step input.
+        actual: StateReference,
+    },
+
+    /// The program rejected the linkage between the step input and the inner pre-state.
+    #[error("program rejected reference linkage: {0}")]
+    InvalidReferenceLinkage(E),
+
+    /// The Moho pre-state commits to a spec that is not part of the spec schedule.
+    #[error("moho pre-state spec commitment {0} is not part of the spec schedule")]
+    UnscheduledSpec(SpecCommitment),
+
+    /// The program rejected the transition.
+    #[error("program rejected transition: {0}")]
+    ProgramRejected(E),
+}
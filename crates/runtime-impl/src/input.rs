@@ -1,12 +1,16 @@
 //! SSZ-encoded input provided to the runtime for computing a single state transition.
 
-use moho_types::MohoState;
+use alloc::vec::Vec;
+
+use moho_runtime_interface::{SpecActivation, SpecSchedule};
+use moho_types::{MohoInstanceId, MohoState, StateReference};
 use ssz_derive::{Decode, Encode};
 
 /// The input required to compute a single incremental state transition.
 ///
-/// Contains the Moho-level pre-state, the SSZ-encoded inner state, and the
-/// SSZ-encoded step input. The runtime deserializes these, runs the
+/// Contains the Moho-level pre-state, the SSZ-encoded inner state, the
+/// SSZ-encoded step input and optionally the reference the previous step produced. The runtime
+/// deserializes these, runs the
 /// [`MohoProgram`](moho_runtime_interface::MohoProgram) transition logic, and
 /// produces a [`StepMohoAttestation`](moho_types::StepMohoAttestation).
 #[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
@@ -25,6 +29,10 @@ pub struct RuntimeInput {
     /// SSZ-encoded step input (`P::StepInput`) that drives the state
     /// transition.
     input_payload: Vec<u8>,
+
+    /// The reference the previous step produced, if known. When set, the runtime rejects step
+    /// inputs whose previous reference differs from it.
+    expected_prev_reference: Option<StateReference>,
 }
 
 impl RuntimeInput {
@@ -37,9 +45,16 @@ impl RuntimeInput {
             moho_pre_state,
             inner_pre_state,
             input_payload,
+            expected_prev_reference: None,
         }
     }
 
+    /// Requires the step input's previous reference to be `reference`.
+    pub fn with_expected_prev_reference(mut self, reference: StateReference) -> Self {
+        self.expected_prev_reference = Some(reference);
+        self
+    }
+
     /// Returns a reference to the Moho pre-state.
     pub fn moho_pre_state(&self) -> &MohoState {
         &self.moho_pre_state
@@ -55,8 +70,114 @@ impl RuntimeInput {
         &self.input_payload
     }
 
+    /// Returns the reference the step input's previous reference must match, if any.
+    pub fn expected_prev_reference(&self) -> Option<&StateReference> {
+        self.expected_prev_reference.as_ref()
+    }
+
     /// Consumes self and returns the Moho pre-state.
     pub fn into_pre_state(self) -> MohoState {
         self.moho_pre_state
     }
 }
+
+/// The input to the step proof program: a [`RuntimeInput`] together with the [`SpecSchedule`]
+/// selecting the [`Spec`](moho_runtime_interface::MohoProgram::Spec) the transition runs under
+/// and the [`MohoInstanceId`] the resulting claim is bound to.
+///
+/// Each is written to the zkVM as separate SSZ-encoded values and read back by
+/// [`process_moho_step`](crate::process_moho_step). The schedule is written as its genesis spec,
+/// the list of its activations and then each upgraded spec.
+#[derive(Clone, Debug)]
+pub struct MohoStepInput<S> {
+    /// The runtime input for the transition.
+    runtime_input: RuntimeInput,
+
+    /// The schedule selecting the spec passed to the program's transition function.
+    schedule: SpecSchedule<S>,
+
+    /// The Moho instance the step claim is bound to.
+    instance: MohoInstanceId,
+}
+
+impl<S> MohoStepInput<S> {
+    /// Creates a new [`MohoStepInput`].
+    pub fn new(
+        runtime_input: RuntimeInput,
+        schedule: SpecSchedule<S>,
+        instance: MohoInstanceId,
+    ) -> Self {
+        Self {
+            runtime_input,
+            schedule,
+            instance,
+        }
+    }
+
+    /// Returns the runtime input for the transition.
+    pub fn runtime_input(&self) -> &RuntimeInput {
+        &self.runtime_input
+    }
+
+    /// Returns the schedule selecting the spec passed to the program's transition function.
+    pub fn schedule(&self) -> &SpecSchedule<S> {
+        &self.schedule
+    }
+
+    /// Returns the Moho instance the step claim is bound to.
+    pub fn instance(&self) -> &MohoInstanceId {
+        &self.instance
+    }
+}
+
+/// SSZ encoding of a [`SpecActivation`], as written to the zkVM for a [`MohoStepInput`].
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct ActivationInput {
+    /// [`ACTIVATION_HEIGHT`] or [`ACTIVATION_REFERENCE`].
+    kind: u8,
+
+    /// The reference, or the little-endian height padded with zeros.
+    value: StateReference,
+}
+
+/// [`ActivationInput::kind`] of a [`SpecActivation::Height`].
+const ACTIVATION_HEIGHT: u8 = 0;
+
+/// [`ActivationInput::kind`] of a [`SpecActivation::Reference`].
+const ACTIVATION_REFERENCE: u8 = 1;
+
+impl From<&SpecActivation> for ActivationInput {
+    fn from(activation: &SpecActivation) -> Self {
+        match activation {
+            SpecActivation::Height(height) => {
+                let mut value = [0; 32];
+                value[..8].copy_from_slice(&height.to_le_bytes());
+                Self {
+                    kind: ACTIVATION_HEIGHT,
+                    value: StateReference::new(value),
+                }
+            }
+            SpecActivation::Reference(reference) => Self {
+                kind: ACTIVATION_REFERENCE,
+                value: *reference,
+            },
+        }
+    }
+}
+
+impl TryFrom<ActivationInput> for SpecActivation {
+    /// The unknown kind.
+    type Error = u8;
+
+    fn try_from(input: ActivationInput) -> Result<Self, Self::Error> {
+        match input.kind {
+            ACTIVATION_HEIGHT => {
+                let mut height = [0; 8];
+                height.copy_from_slice(&input.value.inner()[..8]);
+                Ok(Self::Height(u64::from_le_bytes(height)))
+            }
+            ACTIVATION_REFERENCE => Ok(Self::Reference(input.value)),
+            kind => Err(kind),
+        }
+    }
+}
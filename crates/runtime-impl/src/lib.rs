@@ -7,10 +7,46 @@
 //!
 //! Downstream consumers implement the [`MohoProgram`](moho_runtime_interface::MohoProgram)
 //! trait (defining their inner state types and transition logic), then call
-//! [`compute_moho_attestation`] inside their proof program.
+//! [`compute_moho_attestation`] inside their proof program. Host-side drivers that need to
+//! diagnose bad inputs can use [`try_compute_moho_attestation`] instead, which reports failures
+//! as a [`RuntimeError`] rather than panicking.
+//! [`compute_moho_step`] and [`try_compute_moho_step`] additionally return the post-state as a
+//! [`StepResult`].
+//! All of them select the spec of each transition from a
+//! [`SpecSchedule`](moho_runtime_interface::SpecSchedule) of scheduled upgrades, which holds a
+//! single spec for programs without upgrades.
+//!
+//! [`MohoChainDriver`] runs a program over a stream of step inputs on the host, tracking the
+//! post-states every consumer would otherwise have to recompute.
+//!
+//! [`process_moho_step`] wraps the runtime as a zkVM guest entry point committing a
+//! [`StepMohoClaim`](moho_types::StepMohoClaim), and `MohoStepProgram` exposes it as a
+//! [`ZkVmProgram`](zkaleido::ZkVmProgram) on the host.
+//!
+//! The crate supports `no_std` environments with `alloc` when the default `std` feature is
+//! disabled, in which case only the host-side `MohoStepProgram` is unavailable.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
+mod driver;
+mod errors;
 mod input;
+#[cfg(all(feature = "std", not(target_os = "zkvm")))]
+mod program;
 mod runtime;
+mod step;
+#[cfg(test)]
+mod test_program;
 
-pub use input::RuntimeInput;
-pub use runtime::compute_moho_attestation;
+pub use driver::MohoChainDriver;
+pub use errors::RuntimeError;
+pub use input::{MohoStepInput, RuntimeInput};
+#[cfg(all(feature = "std", not(target_os = "zkvm")))]
+pub use program::MohoStepProgram;
+pub use runtime::{
+    StepResult, compute_moho_attestation, compute_moho_step, try_compute_moho_attestation,
+    try_compute_moho_step,
+};
+pub use step::process_moho_step;
@@ -0,0 +1,144 @@
+//! Host-side [`ZkVmProgram`] for the step proof.
+//!
+//! [`MohoStepProgram`] wraps [`process_moho_step`] for a given [`MohoProgram`], taking a
+//! [`MohoStepInput`] and producing the [`StepMohoClaim`] the guest commits. Its native host runs
+//! the guest logic directly on the host, which lets drivers and tests produce step claims without
+//! a zkVM.
+
+use std::marker::PhantomData;
+
+use moho_runtime_interface::MohoProgram;
+use moho_types::StepMohoClaim;
+use ssz::{Decode, Encode};
+use zkaleido::{ZkVmProgram, ZkVmResult};
+use zkaleido_native_adapter::NativeHost;
+
+use crate::{MohoStepInput, input::ActivationInput, process_moho_step};
+
+/// A host-agnostic ZkVM “program” that encapsulates the step proof logic of a [`MohoProgram`].
+#[derive(Debug)]
+pub struct MohoStepProgram<P>(PhantomData<P>);
+
+impl<P> ZkVmProgram for MohoStepProgram<P>
+where
+    P: MohoProgram,
+    P::Spec: Encode + Decode,
+{
+    type Input = MohoStepInput<P::Spec>;
+    type Output = StepMohoClaim;
+
+    fn name() -> String {
+        "Moho Step".to_string()
+    }
+
+    fn proof_type() -> zkaleido::ProofType {
+        zkaleido::ProofType::Compressed
+    }
+
+    fn prepare_input<'a, B>(input: &'a Self::Input) -> zkaleido::ZkVmInputResult<B::Input>
+    where
+        B: zkaleido::ZkVmInputBuilder<'a>,
+    {
+        let schedule = input.schedule();
+        let activations: Vec<ActivationInput> = schedule
+            .upgrades()
+            .map(|(activation, _)| activation.into())
+            .collect();
+
+        let mut builder = B::new();
+        builder
+            .write_ssz(input.runtime_input())?
+            .write_ssz(schedule.genesis())?
+            .write_ssz(&activations)?;
+        for (_, spec) in schedule.upgrades() {
+            builder.write_ssz(spec)?;
+        }
+        builder.write_ssz(input.instance())?.build()
+    }
+
+    fn process_output<H>(
+        public_values: &zkaleido::PublicValues,
+    ) -> zkaleido::ZkVmResult<Self::Output>
+    where
+        H: zkaleido::ZkVmHost,
+    {
+        H::extract_ssz_public_output(public_values)
+    }
+}
+
+impl<P> MohoStepProgram<P>
+where
+    P: MohoProgram,
+    P::Spec: Encode + Decode,
+{
+    /// Returns the native host for the moho step program
+    pub fn native_host() -> NativeHost {
+        NativeHost::new_with_random_key(process_moho_step::<P>)
+    }
+
+    /// Executes the moho step program in the native mode
+    pub fn execute(
+        input: &<Self as ZkVmProgram>::Input,
+    ) -> ZkVmResult<<Self as ZkVmProgram>::Output> {
+        // Get the native host and delegate to the trait's execute method
+        let host = Self::native_host();
+        let summary = <Self as ZkVmProgram>::execute(input, &host)?;
+        <Self as ZkVmProgram>::process_output::<NativeHost>(summary.public_values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moho_runtime_interface::{SpecActivation, SpecSchedule};
+    use moho_types::{MohoInstanceId, StateReference};
+
+    use super::*;
+    use crate::{compute_moho_attestation, test_program::*};
+
+    #[test]
+    fn test_execute_matches_runtime() {
+        let input = CounterInput::new(1, 3);
+        let runtime_input = runtime_input(counter_moho_state(5), 5, &input);
+
+        let instance = MohoInstanceId::new([0x4d; 32]);
+
+        let expected = compute_moho_attestation::<CounterProgram>(runtime_input.clone(), &SCHEDULE);
+        let output = MohoStepProgram::<CounterProgram>::execute(&MohoStepInput::new(
+            runtime_input,
+            SCHEDULE,
+            instance,
+        ))
+        .unwrap();
+
+        assert_eq!(output, StepMohoClaim::new(instance, expected));
+    }
+
+    #[test]
+    fn test_execute_reads_schedule() {
+        let upgraded = CounterSpec { max_delta: 50 };
+        let schedule = SpecSchedule::new(SPEC)
+            .with_upgrade(SpecActivation::Height(2), CounterSpec { max_delta: 20 })
+            .with_upgrade(
+                SpecActivation::Reference(StateReference::new([2; 32])),
+                upgraded.clone(),
+            );
+        let runtime_input = runtime_input(counter_moho_state(5), 5, &CounterInput::new(1, 3));
+        let instance = MohoInstanceId::new([0x4d; 32]);
+
+        let expected = compute_moho_attestation::<CounterProgram>(runtime_input.clone(), &schedule);
+        let output = MohoStepProgram::<CounterProgram>::execute(&MohoStepInput::new(
+            runtime_input,
+            schedule,
+            instance,
+        ))
+        .unwrap();
+        assert_eq!(output, StepMohoClaim::new(instance, expected));
+
+        // The step reaches both activations, so it runs under the last spec.
+        let under_upgraded = compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &CounterInput::new(1, 3)),
+            &SpecSchedule::new(SPEC).with_upgrade(SpecActivation::Height(0), upgraded),
+        );
+        assert_eq!(output.attestation(), &under_upgraded);
+    }
+}
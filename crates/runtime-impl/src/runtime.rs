@@ -5,50 +5,242 @@
 //! pre-state and SSZ-encoded inner state/input), runs the
 //! [`MohoProgram`] transition logic, and returns a [`StepMohoAttestation`] — the public
 //! parameter required by the recursive proof.
-use moho_runtime_interface::MohoProgram;
-use moho_types::{MohoState, StateRefAttestation, StepMohoAttestation};
-use ssz::Decode;
+//!
+//! [`try_compute_moho_attestation`] is the fallible counterpart, intended for host-side drivers
+//! that need to diagnose bad inputs rather than abort.
+//!
+//! Hosts that need the post-state — to feed the next [`RuntimeInput`], build predicate inclusion
+//! proofs or archive the export state — can use [`compute_moho_step`] or [`try_compute_moho_step`]
+//! instead, which return a [`StepResult`] carrying it alongside the attestation.
+//!
+//! Every entry point selects the spec a transition runs under from a [`SpecSchedule`]. Programs
+//! without scheduled upgrades pass a schedule holding a single spec.
+use alloc::vec::Vec;
+
+use moho_runtime_interface::{MohoProgram, SpecSchedule};
+use moho_types::{ExportOp, MohoState, PendingPredicate, StateRefAttestation, StepMohoAttestation};
+use ssz::{Decode, Encode};
 
-use crate::RuntimeInput;
+use crate::{RuntimeError, RuntimeInput};
 
 /// Computes a [`StepMohoAttestation`] for a single incremental state transition.
 ///
-/// This is the main entry point for the Moho proof runtime. It:
+/// This is the main entry point for the Moho proof runtime. It is a thin wrapper around
+/// [`try_compute_moho_attestation`] that panics on error, which is the desired behaviour inside
+/// a zkVM guest.
+///
+/// # Panics
+///
+/// Panics if [`try_compute_moho_attestation`] returns an error.
+pub fn compute_moho_attestation<P: MohoProgram>(
+    input: RuntimeInput,
+    schedule: &SpecSchedule<P::Spec>,
+) -> StepMohoAttestation {
+    try_compute_moho_attestation::<P>(input, schedule).unwrap_or_else(|e| panic!("runtime: {e}"))
+}
+
+/// Computes a [`StepMohoAttestation`] for a single incremental state transition.
 ///
 /// 1. Deserializes the inner pre-state and step input from the [`RuntimeInput`].
 /// 2. Verifies that the inner pre-state's commitment matches the one stored in the Moho pre-state
 ///    (ensuring consistency between the two state levels).
-/// 3. Executes the program's state transition via [`MohoProgram::process_transition`].
-/// 4. Constructs the post-transition [`MohoState`] with the updated inner state commitment,
-///    predicate, and export state.
-/// 5. Returns a [`StepMohoAttestation`] binding the pre-state reference/commitment to the
+/// 3. Verifies that the step input extends the expected previous reference, if the [`RuntimeInput`]
+///    carries one, and lets the program check that it extends the inner pre-state via
+///    [`MohoProgram::check_reference_linkage`].
+/// 4. Selects the spec the transition runs under from `schedule`, see below, and executes the
+///    program's state transition under it via [`MohoProgram::process_transition`].
+/// 5. Constructs the post-transition [`MohoState`] with the updated inner state commitment,
+///    predicates, export state and the commitment to the selected spec. A pending predicate whose
+///    notice period ends with this step becomes the next predicate, and a predicate produced by the
+///    transition is either activated immediately or scheduled as pending, depending on
+///    [`MohoProgram::PREDICATE_ACTIVATION_DELAY`]. The export state is obtained by applying the
+///    operations returned by [`MohoProgram::compute_export_ops`] to the pre-state's, see below.
+/// 6. Returns a [`StepMohoAttestation`] binding the pre-state reference/commitment to the
 ///    post-state reference/commitment. This attestation serves as the public parameter for the
 ///    recursive proof.
 ///
+/// # Spec selection
+///
+/// The transition runs under the spec [`SpecSchedule::select`] returns for the Moho pre-state's
+/// spec commitment and the step input. The post-state commits to that spec, so a chain crossing
+/// an activation point keeps running under the upgraded spec. A schedule holding a single spec
+/// runs every transition under it, and only accepts pre-states committing to it.
+///
+/// # Export state invariants
+///
+/// Bridges rely on exported entries never being retracted. Programs therefore do not return the
+/// post-transition export state but a list of [`ExportOp`]s, which the runtime applies to the
+/// pre-state's export state. Operations can only create containers, append entries and update
+/// extra data, so the post-state's export state extends the pre-state's by construction.
+///
+/// # Errors
+///
+/// Returns a [`RuntimeError`] if deserialization fails, if the inner pre-state commitment does
+/// not match the commitment in the Moho pre-state, if the step input does not extend the expected
+/// previous reference, if the program rejects the reference linkage or the transition, or if the
+/// program's export operations cannot be applied. Returns [`RuntimeError::UnscheduledSpec`] if
+/// the Moho pre-state commits to a spec that is not part of `schedule`.
+pub fn try_compute_moho_attestation<P: MohoProgram>(
+    input: RuntimeInput,
+    schedule: &SpecSchedule<P::Spec>,
+) -> Result<StepMohoAttestation, RuntimeError<P::Error>> {
+    try_compute_step::<P>(input, schedule).map(|outcome| outcome.attestation)
+}
+
+/// The full result of a single incremental state transition.
+///
+/// Returned by [`compute_moho_step`] and [`try_compute_moho_step`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepResult {
+    /// The attestation binding the pre-state to the post-state.
+    attestation: StepMohoAttestation,
+
+    /// The [`MohoState`] after the transition, committed to by the attestation.
+    post_state: MohoState,
+
+    /// SSZ-encoded inner state (`P::State`) after the transition.
+    inner_post_state: Vec<u8>,
+
+    /// Whether the next predicate changed in the transition.
+    predicate_updated: bool,
+
+    /// The operations the transition applied to the export state.
+    export_ops: Vec<ExportOp>,
+}
+
+impl StepResult {
+    /// Returns the attestation binding the pre-state to the post-state.
+    pub fn attestation(&self) -> &StepMohoAttestation {
+        &self.attestation
+    }
+
+    /// Returns the Moho state after the transition.
+    pub fn post_state(&self) -> &MohoState {
+        &self.post_state
+    }
+
+    /// Returns the SSZ-encoded inner state after the transition.
+    pub fn inner_post_state(&self) -> &[u8] {
+        &self.inner_post_state
+    }
+
+    /// Returns whether the next predicate changed in the transition, either because the
+    /// transition produced one that activates immediately or because a pending predicate
+    /// activated.
+    pub fn predicate_updated(&self) -> bool {
+        self.predicate_updated
+    }
+
+    /// Returns the operations the transition applied to the export state, in order.
+    pub fn export_ops(&self) -> &[ExportOp] {
+        &self.export_ops
+    }
+
+    /// Consumes self and returns the attestation.
+    pub fn into_attestation(self) -> StepMohoAttestation {
+        self.attestation
+    }
+
+    /// Consumes self and returns the attestation, the Moho post-state and the SSZ-encoded inner
+    /// post-state.
+    pub fn into_parts(self) -> (StepMohoAttestation, MohoState, Vec<u8>) {
+        (self.attestation, self.post_state, self.inner_post_state)
+    }
+}
+
+/// Computes a [`StepResult`] for a single incremental state transition.
+///
+/// Like [`compute_moho_attestation`], but also returns the post-state.
+///
 /// # Panics
 ///
-/// Panics if deserialization fails or if the inner pre-state commitment does not match
-/// the commitment in the Moho pre-state.
-pub fn compute_moho_attestation<P: MohoProgram>(
+/// Panics if [`try_compute_moho_step`] returns an error.
+pub fn compute_moho_step<P: MohoProgram>(
     input: RuntimeInput,
-    spec: &P::Spec,
-) -> StepMohoAttestation {
-    let inner_pre_state =
-        P::State::from_ssz_bytes(input.inner_pre_state()).expect("runtime: deserialize pre state");
+    schedule: &SpecSchedule<P::Spec>,
+) -> StepResult {
+    try_compute_moho_step::<P>(input, schedule).unwrap_or_else(|e| panic!("runtime: {e}"))
+}
+
+/// Computes a [`StepResult`] for a single incremental state transition.
+///
+/// Runs the same steps as [`try_compute_moho_attestation`], producing the same attestation, but
+/// also returns the post-transition [`MohoState`] and SSZ-encoded inner state so that callers can
+/// continue from them without re-running the program.
+///
+/// # Errors
+///
+/// Returns a [`RuntimeError`] under the same conditions as [`try_compute_moho_attestation`].
+pub fn try_compute_moho_step<P: MohoProgram>(
+    input: RuntimeInput,
+    schedule: &SpecSchedule<P::Spec>,
+) -> Result<StepResult, RuntimeError<P::Error>> {
+    let outcome = try_compute_step::<P>(input, schedule)?;
+    Ok(StepResult {
+        inner_post_state: P::extract_post_state(&outcome.output).as_ssz_bytes(),
+        attestation: outcome.attestation,
+        post_state: outcome.post_state,
+        predicate_updated: outcome.predicate_updated,
+        export_ops: outcome.export_ops,
+    })
+}
+
+/// The outcome of [`try_compute_step`].
+///
+/// Unlike [`StepResult`], this keeps the program's step output as is, so that the guest path
+/// does not pay for re-encoding the inner post-state.
+pub(crate) struct StepOutcome<O> {
+    pub(crate) attestation: StepMohoAttestation,
+    pub(crate) post_state: MohoState,
+    pub(crate) output: O,
+    pub(crate) predicate_updated: bool,
+    pub(crate) export_ops: Vec<ExportOp>,
+}
+
+/// Executes a single incremental state transition, as described in
+/// [`try_compute_moho_attestation`].
+pub(crate) fn try_compute_step<P: MohoProgram>(
+    input: RuntimeInput,
+    schedule: &SpecSchedule<P::Spec>,
+) -> Result<StepOutcome<P::StepOutput>, RuntimeError<P::Error>> {
+    let inner_pre_state = P::State::from_ssz_bytes(input.inner_pre_state())
+        .map_err(RuntimeError::InnerStateDecode)?;
     let inner_input = deserialize_ssz::<P::StepInput>(input.input_payload())
-        .expect("runtime: deserialize inner input");
+        .map_err(RuntimeError::StepInputDecode)?;
 
     // Verify that the provided inner pre-state is consistent with the Moho pre-state
     // by checking that its commitment matches the one stored in `moho_pre_state`.
     let pre_inner_state_commitment = P::compute_state_commitment(&inner_pre_state);
-    assert_eq!(
-        pre_inner_state_commitment,
-        input.moho_pre_state().inner_state(),
-        "runtime: inner pre-state commitment does not match moho pre-state"
-    );
+    let expected_inner_state_commitment = input.moho_pre_state().inner_state();
+    if pre_inner_state_commitment != expected_inner_state_commitment {
+        return Err(RuntimeError::InnerStateCommitmentMismatch {
+            expected: expected_inner_state_commitment,
+            computed: pre_inner_state_commitment,
+        });
+    }
+
+    // Reject step inputs that do not extend the previous step before running the transition.
+    let prev_reference = P::extract_prev_reference(&inner_input);
+    if let Some(expected) = input.expected_prev_reference()
+        && *expected != prev_reference
+    {
+        return Err(RuntimeError::ReferenceMismatch {
+            expected: *expected,
+            actual: prev_reference,
+        });
+    }
+    P::check_reference_linkage(&inner_pre_state, &inner_input)
+        .map_err(RuntimeError::InvalidReferenceLinkage)?;
+
+    // Select the spec the transition runs under.
+    let current = input.moho_pre_state().spec_commitment();
+    let spec = schedule
+        .select::<P>(&current, &inner_input)
+        .ok_or(RuntimeError::UnscheduledSpec(current))?;
 
     // Execute the inner state transition.
-    let step_output = P::process_transition(&inner_pre_state, spec, &inner_input);
+    let step_output = P::process_transition(&inner_pre_state, spec, &inner_input)
+        .map_err(RuntimeError::ProgramRejected)?;
 
     // Compute the post-transition inner state commitment.
     let inner_post_state = P::extract_post_state(&step_output);
@@ -56,10 +248,9 @@ pub fn compute_moho_attestation<P: MohoProgram>(
 
     // Build the pre-state half of the attestation before consuming the input,
     // so we can move `moho_pre_state` fields without cloning.
-    let pre_state_attestation = StateRefAttestation::new(
-        P::extract_prev_reference(&inner_input),
-        input.moho_pre_state().compute_commitment(),
-    );
+    let pre_state_attestation =
+        StateRefAttestation::new(prev_reference, input.moho_pre_state().compute_commitment());
+    let pre_pending_predicate = input.moho_pre_state().pending_predicate().cloned();
 
     // Destructure the owned moho pre-state to avoid cloning.
     let MohoState {
@@ -68,26 +259,545 @@ pub fn compute_moho_attestation<P: MohoProgram>(
         ..
     } = input.into_pre_state();
 
-    // Determine the next predicate: use updated key if the transition produced one,
-    // otherwise carry forward the current predicate.
-    let next_predicate = match P::extract_next_predicate(&step_output) {
-        Some(new_key) => new_key,
-        None => pre_next_predicate,
-    };
+    // Count down the pending predicate, activating it once its notice period is over.
+    let (mut next_predicate, mut pending_predicate, mut predicate_updated) =
+        match pre_pending_predicate {
+            Some(pending) if pending.remaining_steps() <= 1 => {
+                (pending.into_predicate(), None, true)
+            }
+            Some(pending) => {
+                let remaining_steps = pending.remaining_steps() - 1;
+                let pending = PendingPredicate::new(pending.into_predicate(), remaining_steps);
+                (pre_next_predicate, Some(pending), false)
+            }
+            None => (pre_next_predicate, None, false),
+        };
+
+    // A key produced by the transition replaces any pending one, and either becomes the next
+    // predicate right away or after the program's activation delay.
+    if let Some(predicate) = P::extract_next_predicate(&step_output) {
+        if P::PREDICATE_ACTIVATION_DELAY == 0 {
+            next_predicate = predicate;
+            pending_predicate = None;
+            predicate_updated = true;
+        } else {
+            pending_predicate = Some(PendingPredicate::new(
+                predicate,
+                P::PREDICATE_ACTIVATION_DELAY,
+            ));
+        }
+    }
 
-    // Compute the updated export state and assemble the post-transition MohoState.
-    let export_state = P::compute_next_export_state(pre_export_state, &step_output);
-    let post_moho_state = MohoState::new(post_inner_state_commitment, next_predicate, export_state);
+    // Apply the program's export operations to the pre-state's export state and assemble the
+    // post-transition MohoState, binding the spec the transition ran under.
+    let export_ops = P::compute_export_ops(&pre_export_state, &step_output);
+    let mut export_state = pre_export_state;
+    for op in &export_ops {
+        export_state
+            .apply(op)
+            .map_err(RuntimeError::InvalidExportOp)?;
+    }
+    let mut post_moho_state = MohoState::new(
+        post_inner_state_commitment,
+        next_predicate,
+        export_state,
+        P::compute_spec_commitment(spec),
+    );
+    if let Some(pending) = pending_predicate {
+        post_moho_state = post_moho_state.with_pending_predicate(pending);
+    }
 
     // Build the post-state half of the attestation.
     let post_state_attestation = StateRefAttestation::new(
         P::compute_input_reference(&inner_input),
         post_moho_state.compute_commitment(),
     );
-    StepMohoAttestation::new(pre_state_attestation, post_state_attestation)
+    Ok(StepOutcome {
+        attestation: StepMohoAttestation::new(pre_state_attestation, post_state_attestation),
+        post_state: post_moho_state,
+        output: step_output,
+        predicate_updated,
+        export_ops,
+    })
 }
 
 /// Deserializes an SSZ-encoded value from a byte slice.
 fn deserialize_ssz<T: Decode>(buf: &[u8]) -> Result<T, ssz::DecodeError> {
     T::from_ssz_bytes(buf)
 }
+
+#[cfg(test)]
+mod tests {
+    use moho_runtime_interface::SpecActivation;
+    use moho_types::{ExportContainer, ExportState, PendingPredicate, StateReference};
+    use strata_predicate::PredicateKey;
+
+    use super::*;
+    use crate::test_program::*;
+
+    #[test]
+    fn test_compute_attestation() {
+        let pre_state = counter_moho_state(5);
+        let input = CounterInput::new(1, 3);
+
+        let att = compute_moho_attestation::<CounterProgram>(
+            runtime_input(pre_state.clone(), 5, &input),
+            &SCHEDULE,
+        );
+
+        assert_eq!(*att.from().reference(), StateReference::new([1; 32]));
+        assert_eq!(*att.from().commitment(), pre_state.compute_commitment());
+        assert_eq!(*att.to().reference(), StateReference::new([2; 32]));
+        assert_ne!(*att.to().commitment(), pre_state.compute_commitment());
+    }
+
+    #[test]
+    fn test_try_compute_inner_state_decode_error() {
+        let input = CounterInput::new(1, 3);
+        let runtime_input =
+            RuntimeInput::new(counter_moho_state(5), vec![0xFF], input.as_ssz_bytes());
+
+        let res = try_compute_moho_attestation::<CounterProgram>(runtime_input, &SCHEDULE);
+        assert!(matches!(res, Err(RuntimeError::InnerStateDecode(_))));
+    }
+
+    #[test]
+    fn test_try_compute_step_input_decode_error() {
+        let runtime_input =
+            RuntimeInput::new(counter_moho_state(5), 5u64.as_ssz_bytes(), vec![0xFF]);
+
+        let res = try_compute_moho_attestation::<CounterProgram>(runtime_input, &SCHEDULE);
+        assert!(matches!(res, Err(RuntimeError::StepInputDecode(_))));
+    }
+
+    #[test]
+    fn test_try_compute_commitment_mismatch() {
+        let input = CounterInput::new(1, 3);
+        let res = try_compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 6, &input),
+            &SCHEDULE,
+        );
+
+        match res {
+            Err(RuntimeError::InnerStateCommitmentMismatch { expected, computed }) => {
+                assert_eq!(expected, CounterProgram::compute_state_commitment(&5));
+                assert_eq!(computed, CounterProgram::compute_state_commitment(&6));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_compute_step_reports_export_ops() {
+        let result = compute_moho_step::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &CounterInput::new(1, 3)),
+            &SCHEDULE,
+        );
+        assert_eq!(
+            result.export_ops(),
+            [ExportOp::AddEntry {
+                container_id: COUNTER_CONTAINER_ID,
+                entry: counter_entry(8),
+            }]
+        );
+
+        let mut container = ExportContainer::new(COUNTER_CONTAINER_ID);
+        container.add_entry(counter_entry(8)).unwrap();
+        assert_eq!(
+            result
+                .post_state()
+                .export_state()
+                .get_container(COUNTER_CONTAINER_ID),
+            Some(&container)
+        );
+    }
+
+    #[test]
+    fn test_compute_step_cannot_rewrite_export_history() {
+        let first = compute_moho_step::<CounterProgram>(
+            runtime_input(counter_moho_state(0), 0, &CounterInput::new(0, 1)),
+            &SCHEDULE,
+        );
+        let second = compute_moho_step::<CounterProgram>(
+            runtime_input(first.post_state().clone(), 1, &CounterInput::new(1, 1)),
+            &SCHEDULE,
+        );
+        let post_state = second.post_state();
+
+        // The export state is the previous one with the new entry appended.
+        let mut expected = first.post_state().export_state().clone();
+        expected
+            .add_entry(COUNTER_CONTAINER_ID, counter_entry(2))
+            .unwrap();
+        assert_eq!(post_state.export_state(), &expected);
+
+        // An MMR replacing the previous history and holding one more leaf cannot be told apart
+        // from the honest one by its size, but a post-state carrying it does not match the
+        // attestation.
+        let mut rewritten = ExportState::new(vec![]).unwrap();
+        rewritten
+            .add_entry(COUNTER_CONTAINER_ID, [0xFF; 32])
+            .unwrap();
+        rewritten
+            .add_entry(COUNTER_CONTAINER_ID, counter_entry(2))
+            .unwrap();
+        assert!(rewritten.diff(first.post_state().export_state()).is_ok());
+
+        let forged = MohoState::new(
+            post_state.inner_state(),
+            post_state.next_predicate().clone(),
+            rewritten,
+            post_state.spec_commitment(),
+        );
+        assert_ne!(forged, *post_state);
+        assert_ne!(
+            forged.compute_commitment(),
+            *second.attestation().to().commitment()
+        );
+    }
+
+    #[test]
+    fn test_try_compute_program_rejected() {
+        let input = CounterInput::new(1, 0);
+        let res = try_compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+        assert!(matches!(res, Err(RuntimeError::ProgramRejected(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "program rejected transition")]
+    fn test_compute_panics_on_rejection() {
+        let input = CounterInput::new(1, 0);
+        compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+    }
+
+    #[test]
+    fn test_compute_step_matches_attestation() {
+        let pre_state = counter_moho_state(5);
+        let input = CounterInput::new(1, 3);
+        let runtime_input = runtime_input(pre_state, 5, &input);
+
+        let expected = compute_moho_attestation::<CounterProgram>(runtime_input.clone(), &SCHEDULE);
+        let result = compute_moho_step::<CounterProgram>(runtime_input, &SCHEDULE);
+
+        assert_eq!(*result.attestation(), expected);
+        assert_eq!(
+            *expected.to().commitment(),
+            result.post_state().compute_commitment()
+        );
+        assert_eq!(result.inner_post_state(), 8u64.as_ssz_bytes());
+        assert_eq!(
+            result.post_state().inner_state(),
+            CounterProgram::compute_state_commitment(&8)
+        );
+        assert!(!result.predicate_updated());
+        assert_eq!(result.post_state().next_predicate(), &always_accept());
+    }
+
+    #[test]
+    fn test_compute_step_predicate_updated() {
+        // Any predicate key distinct from the pre-state's always-accept key.
+        let new_predicate = PredicateKey {
+            id: 1,
+            condition: vec![0x42].try_into().unwrap(),
+        };
+        let mut input = CounterInput::new(1, 3);
+        input.next_predicate = Some(new_predicate.clone());
+
+        let result = compute_moho_step::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+
+        assert!(result.predicate_updated());
+        assert_eq!(result.post_state().next_predicate(), &new_predicate);
+    }
+
+    #[test]
+    fn test_try_compute_step_program_rejected() {
+        let input = CounterInput::new(1, 0);
+        let res = try_compute_moho_step::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+        assert!(matches!(res, Err(RuntimeError::ProgramRejected(_))));
+    }
+
+    #[test]
+    fn test_try_compute_expected_prev_reference() {
+        let input = CounterInput::new(1, 3);
+
+        let matching = runtime_input(counter_moho_state(5), 5, &input)
+            .with_expected_prev_reference(StateReference::new([1; 32]));
+        assert!(try_compute_moho_attestation::<CounterProgram>(matching, &SCHEDULE).is_ok());
+
+        let mismatching = runtime_input(counter_moho_state(5), 5, &input)
+            .with_expected_prev_reference(StateReference::new([2; 32]));
+        match try_compute_moho_attestation::<CounterProgram>(mismatching, &SCHEDULE) {
+            Err(RuntimeError::ReferenceMismatch { expected, actual }) => {
+                assert_eq!(expected, StateReference::new([2; 32]));
+                assert_eq!(actual, StateReference::new([1; 32]));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_try_compute_invalid_reference_linkage() {
+        let mut input = CounterInput::new(1, 3);
+        input.reference = input.prev;
+
+        let res = try_compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+        assert!(matches!(res, Err(RuntimeError::InvalidReferenceLinkage(_))));
+    }
+
+    #[test]
+    fn test_try_compute_rejects_input_not_extending_pre_state() {
+        let mut input = CounterInput::new(1, 3);
+        input.parent = Some(5);
+        let res = try_compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+        assert!(res.is_ok());
+
+        // The input builds on a counter other than the pre-state's.
+        input.parent = Some(4);
+        let res = try_compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+        assert!(matches!(
+            res,
+            Err(RuntimeError::InvalidReferenceLinkage(
+                "input does not extend the counter"
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_compute_step_commits_to_spec() {
+        let input = CounterInput::new(1, 3);
+        let result = compute_moho_step::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+        assert_eq!(
+            result.post_state().spec_commitment(),
+            CounterProgram::compute_spec_commitment(&SPEC)
+        );
+
+        // The same transition upgrading to a different spec leads to a different state.
+        let schedule = SpecSchedule::new(SPEC)
+            .with_upgrade(SpecActivation::Height(0), CounterSpec { max_delta: 50 });
+        let other = compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &schedule,
+        );
+        assert_eq!(other.from(), result.attestation().from());
+        assert_ne!(other.to(), result.attestation().to());
+    }
+
+    /// A predicate key distinct from the always-accept key of [`counter_moho_state`].
+    fn rotated_predicate() -> PredicateKey {
+        PredicateKey {
+            id: 1,
+            condition: vec![0x42].try_into().unwrap(),
+        }
+    }
+
+    /// Applies `input` to the given states under a [`DelayedCounterProgram`] with
+    /// [`PREDICATE_DELAY`], returning the step result.
+    fn apply_delayed(moho_state: MohoState, counter: u64, input: &CounterInput) -> StepResult {
+        compute_moho_step::<DelayedCounterProgram<PREDICATE_DELAY>>(
+            runtime_input(moho_state, counter, input),
+            &SCHEDULE,
+        )
+    }
+
+    #[test]
+    fn test_delayed_predicate_activation() {
+        let mut input = CounterInput::new(0, 1);
+        input.next_predicate = Some(rotated_predicate());
+
+        // The rotated key is recorded as pending while the current one stays in effect.
+        let result = apply_delayed(counter_moho_state(0), 0, &input);
+        assert!(!result.predicate_updated());
+        assert_eq!(result.post_state().next_predicate(), &always_accept());
+        assert_eq!(
+            result.post_state().pending_predicate(),
+            Some(&PendingPredicate::new(rotated_predicate(), PREDICATE_DELAY))
+        );
+
+        let mut state = result.post_state().clone();
+        for step in 1..PREDICATE_DELAY {
+            let result = apply_delayed(state, step, &CounterInput::new(step as u8, 1));
+            assert!(!result.predicate_updated());
+            assert_eq!(result.post_state().next_predicate(), &always_accept());
+            assert_eq!(
+                result
+                    .post_state()
+                    .pending_predicate()
+                    .unwrap()
+                    .remaining_steps(),
+                PREDICATE_DELAY - step
+            );
+            state = result.post_state().clone();
+        }
+
+        // The step ending the notice period activates it.
+        let step = PREDICATE_DELAY;
+        let result = apply_delayed(state, step, &CounterInput::new(step as u8, 1));
+        assert!(result.predicate_updated());
+        assert_eq!(result.post_state().next_predicate(), &rotated_predicate());
+        assert!(result.post_state().pending_predicate().is_none());
+    }
+
+    #[test]
+    fn test_delayed_predicate_replaced_by_newer_update() {
+        let mut input = CounterInput::new(0, 1);
+        input.next_predicate = Some(rotated_predicate());
+        let result = apply_delayed(counter_moho_state(0), 0, &input);
+
+        // A newer update restarts the notice period.
+        let mut input = CounterInput::new(1, 1);
+        input.next_predicate = Some(always_accept());
+        let result = apply_delayed(result.post_state().clone(), 1, &input);
+        assert_eq!(
+            result.post_state().pending_predicate(),
+            Some(&PendingPredicate::new(always_accept(), PREDICATE_DELAY))
+        );
+    }
+
+    #[test]
+    fn test_pending_predicate_activates_without_delay() {
+        // Pending predicates are counted down even by programs activating updates immediately.
+        let pre_state = counter_moho_state(5)
+            .with_pending_predicate(PendingPredicate::new(rotated_predicate(), 1));
+        let result = compute_moho_step::<CounterProgram>(
+            runtime_input(pre_state, 5, &CounterInput::new(1, 3)),
+            &SCHEDULE,
+        );
+        assert!(result.predicate_updated());
+        assert_eq!(result.post_state().next_predicate(), &rotated_predicate());
+        assert!(result.post_state().pending_predicate().is_none());
+    }
+
+    /// The spec [`SPEC`] is upgraded to in schedule tests.
+    const UPGRADED_SPEC: CounterSpec = CounterSpec { max_delta: 5 };
+
+    /// Applies `input` to the given states under `schedule`, returning the post-states.
+    fn apply_scheduled(
+        schedule: &SpecSchedule<CounterSpec>,
+        moho_state: MohoState,
+        counter: u64,
+        input: &CounterInput,
+    ) -> Result<(MohoState, u64), RuntimeError<&'static str>> {
+        let result = try_compute_moho_step::<CounterProgram>(
+            runtime_input(moho_state, counter, input),
+            schedule,
+        )?;
+        let (_, moho_state, inner_state) = result.into_parts();
+        Ok((moho_state, u64::from_ssz_bytes(&inner_state).unwrap()))
+    }
+
+    #[test]
+    fn test_schedule_crosses_height_activation() {
+        let schedule =
+            SpecSchedule::new(SPEC).with_upgrade(SpecActivation::Height(3), UPGRADED_SPEC);
+        let genesis_commitment = CounterProgram::compute_spec_commitment(&SPEC);
+        let upgraded_commitment = CounterProgram::compute_spec_commitment(&UPGRADED_SPEC);
+
+        // Heights 1 and 2 run under the genesis spec.
+        let (mut state, mut counter) = (counter_moho_state(0), 0);
+        for prev in 0..2 {
+            (state, counter) =
+                apply_scheduled(&schedule, state, counter, &CounterInput::new(prev, 10)).unwrap();
+            assert_eq!(state.spec_commitment(), genesis_commitment);
+        }
+
+        // Height 3 activates the upgraded spec, which rejects the previous delta.
+        let res = apply_scheduled(&schedule, state.clone(), counter, &CounterInput::new(2, 10));
+        assert!(matches!(res, Err(RuntimeError::ProgramRejected(_))));
+
+        (state, counter) =
+            apply_scheduled(&schedule, state, counter, &CounterInput::new(2, 5)).unwrap();
+        assert_eq!(state.spec_commitment(), upgraded_commitment);
+
+        // Later heights keep running under the upgraded spec.
+        let res = apply_scheduled(&schedule, state.clone(), counter, &CounterInput::new(3, 10));
+        assert!(matches!(res, Err(RuntimeError::ProgramRejected(_))));
+        let (state, counter) =
+            apply_scheduled(&schedule, state, counter, &CounterInput::new(3, 1)).unwrap();
+        assert_eq!(state.spec_commitment(), upgraded_commitment);
+        assert_eq!(counter, 26);
+    }
+
+    #[test]
+    fn test_schedule_reference_activation() {
+        let schedule = SpecSchedule::new(SPEC).with_upgrade(
+            SpecActivation::Reference(StateReference::new([2; 32])),
+            UPGRADED_SPEC,
+        );
+
+        let (state, counter) = apply_scheduled(
+            &schedule,
+            counter_moho_state(0),
+            0,
+            &CounterInput::new(0, 1),
+        )
+        .unwrap();
+        assert_eq!(
+            state.spec_commitment(),
+            CounterProgram::compute_spec_commitment(&SPEC)
+        );
+
+        // The step producing the activation reference runs under the upgraded spec.
+        let (state, _) =
+            apply_scheduled(&schedule, state, counter, &CounterInput::new(1, 1)).unwrap();
+        assert_eq!(
+            state.spec_commitment(),
+            CounterProgram::compute_spec_commitment(&UPGRADED_SPEC)
+        );
+    }
+
+    #[test]
+    fn test_schedule_matches_genesis_spec_before_activation() {
+        let schedule =
+            SpecSchedule::new(SPEC).with_upgrade(SpecActivation::Height(10), UPGRADED_SPEC);
+        let input = CounterInput::new(1, 3);
+
+        let genesis = compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &SCHEDULE,
+        );
+        let scheduled = compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &input),
+            &schedule,
+        );
+        assert_eq!(genesis, scheduled);
+    }
+
+    #[test]
+    fn test_schedule_rejects_unscheduled_spec() {
+        let schedule = SpecSchedule::new(UPGRADED_SPEC);
+        let res = try_compute_moho_attestation::<CounterProgram>(
+            runtime_input(counter_moho_state(5), 5, &CounterInput::new(1, 3)),
+            &schedule,
+        );
+        match res {
+            Err(RuntimeError::UnscheduledSpec(commitment)) => {
+                assert_eq!(commitment, CounterProgram::compute_spec_commitment(&SPEC));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
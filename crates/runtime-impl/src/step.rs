@@ -0,0 +1,52 @@
+//! zkVM entry point for the step proof.
+
+use alloc::vec::Vec;
+
+use moho_runtime_interface::{MohoProgram, SpecActivation, SpecSchedule};
+use moho_types::{MohoInstanceId, StepMohoClaim};
+use ssz::{Decode, Encode};
+use zkaleido::ZkVmEnv;
+
+use crate::{RuntimeInput, compute_moho_attestation, input::ActivationInput};
+
+/// Reads an SSZ-encoded [`RuntimeInput`], spec schedule and [`MohoInstanceId`] from the zkVM,
+/// computes the step attestation and commits it back to the zkVM as a [`StepMohoClaim`] of that
+/// instance.
+///
+/// The schedule is read as written by [`MohoStepInput`](crate::MohoStepInput).
+///
+/// # Panics
+///
+/// Panics if decoding the input fails or if the transition is invalid.
+pub fn process_moho_step<P>(zkvm: &impl ZkVmEnv)
+where
+    P: MohoProgram,
+    P::Spec: Encode + Decode,
+{
+    let input: RuntimeInput = read_ssz(zkvm);
+    let schedule = read_schedule::<P::Spec>(zkvm);
+    let instance: MohoInstanceId = read_ssz(zkvm);
+
+    let attestation = compute_moho_attestation::<P>(input, &schedule);
+
+    zkvm.commit_buf(&StepMohoClaim::new(instance, attestation).as_ssz_bytes());
+}
+
+/// Reads a [`SpecSchedule`] from the zkVM: its genesis spec, the list of its activations and then
+/// each upgraded spec.
+fn read_schedule<S: Decode>(zkvm: &impl ZkVmEnv) -> SpecSchedule<S> {
+    let genesis: S = read_ssz(zkvm);
+    let activations: Vec<ActivationInput> = read_ssz(zkvm);
+    activations
+        .into_iter()
+        .fold(SpecSchedule::new(genesis), |schedule, activation| {
+            let activation = SpecActivation::try_from(activation)
+                .unwrap_or_else(|kind| panic!("step: unknown spec activation kind {kind}"));
+            schedule.with_upgrade(activation, read_ssz(zkvm))
+        })
+}
+
+/// Reads the next input buffer from the zkVM and decodes it as SSZ.
+fn read_ssz<T: Decode>(zkvm: &impl ZkVmEnv) -> T {
+    T::from_ssz_bytes(&zkvm.read_buf()).unwrap_or_else(|e| panic!("step: decode input: {e:?}"))
+}
@@ -0,0 +1,179 @@
+//! A minimal [`MohoProgram`] used to exercise the runtime in tests.
+//!
+//! The inner state is a running counter. Each step input adds a non-zero `delta`, bounded by the
+//! spec's `max_delta`, to it, exports the new counter value to container [`COUNTER_CONTAINER_ID`]
+//! and optionally rotates the next predicate. A step input must not reuse its previous reference,
+//! and may name the counter value it builds on, which must match the pre-state.
+//! The height of a step input is the first byte of the reference it produces.
+
+use moho_runtime_interface::{MohoProgram, SpecSchedule};
+pub(crate) use moho_types::test_utils::always_accept;
+use moho_types::{
+    ExportOp, ExportState, InnerStateCommitment, MohoState, SpecCommitment, StateReference,
+};
+use ssz::Encode;
+use ssz_derive::{Decode, Encode};
+use strata_predicate::PredicateKey;
+
+use crate::RuntimeInput;
+
+/// Export container the counter program appends to.
+pub(crate) const COUNTER_CONTAINER_ID: u8 = 7;
+
+/// Spec for [`CounterProgram`].
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct CounterSpec {
+    pub(crate) max_delta: u64,
+}
+
+/// The spec used by tests unless they exercise spec handling.
+pub(crate) const SPEC: CounterSpec = CounterSpec { max_delta: 100 };
+
+/// The schedule running every transition under [`SPEC`].
+pub(crate) const SCHEDULE: SpecSchedule<CounterSpec> = SpecSchedule::new(SPEC);
+
+/// Step input for [`CounterProgram`].
+#[derive(Clone, Debug, Encode, Decode)]
+pub(crate) struct CounterInput {
+    pub(crate) prev: StateReference,
+    pub(crate) reference: StateReference,
+    pub(crate) delta: u64,
+    pub(crate) next_predicate: Option<PredicateKey>,
+    pub(crate) parent: Option<u64>,
+}
+
+impl CounterInput {
+    /// Creates an input moving from reference `prev` to reference `prev + 1`.
+    pub(crate) fn new(prev: u8, delta: u64) -> Self {
+        Self {
+            prev: StateReference::new([prev; 32]),
+            reference: StateReference::new([prev + 1; 32]),
+            delta,
+            next_predicate: None,
+            parent: None,
+        }
+    }
+}
+
+/// Output of a [`CounterProgram`] step.
+#[derive(Debug)]
+pub(crate) struct CounterOutput {
+    state: u64,
+    next_predicate: Option<PredicateKey>,
+}
+
+/// A [`MohoProgram`] whose inner state is a `u64` counter and whose predicate updates take effect
+/// after `DELAY` steps.
+#[derive(Debug)]
+pub(crate) struct DelayedCounterProgram<const DELAY: u64>;
+
+/// A [`DelayedCounterProgram`] activating predicate updates immediately.
+pub(crate) type CounterProgram = DelayedCounterProgram<0>;
+
+impl<const DELAY: u64> MohoProgram for DelayedCounterProgram<DELAY> {
+    type State = u64;
+    type StepInput = CounterInput;
+    type Spec = CounterSpec;
+    type StepOutput = CounterOutput;
+    type Error = &'static str;
+
+    const PREDICATE_ACTIVATION_DELAY: u64 = DELAY;
+
+    fn compute_input_reference(input: &Self::StepInput) -> StateReference {
+        input.reference
+    }
+
+    fn extract_prev_reference(input: &Self::StepInput) -> StateReference {
+        input.prev
+    }
+
+    fn check_reference_linkage(
+        pre_state: &Self::State,
+        inp: &Self::StepInput,
+    ) -> Result<(), Self::Error> {
+        if inp.reference == inp.prev {
+            return Err("reference must advance");
+        }
+        if inp.parent.is_some_and(|parent| parent != *pre_state) {
+            return Err("input does not extend the counter");
+        }
+        Ok(())
+    }
+
+    fn extract_height(input: &Self::StepInput) -> Option<u64> {
+        Some(u64::from(input.reference.inner()[0]))
+    }
+
+    fn compute_state_commitment(state: &Self::State) -> InnerStateCommitment {
+        let mut buf = [0u8; 32];
+        buf[..8].copy_from_slice(&state.to_le_bytes());
+        InnerStateCommitment::new(buf)
+    }
+
+    fn compute_spec_commitment(spec: &Self::Spec) -> SpecCommitment {
+        let mut buf = [0u8; 32];
+        buf[..8].copy_from_slice(&spec.max_delta.to_le_bytes());
+        SpecCommitment::new(buf)
+    }
+
+    fn process_transition(
+        pre_state: &Self::State,
+        spec: &Self::Spec,
+        inp: &Self::StepInput,
+    ) -> Result<Self::StepOutput, Self::Error> {
+        if inp.delta == 0 {
+            return Err("delta must be non-zero");
+        }
+        if inp.delta > spec.max_delta {
+            return Err("delta exceeds max_delta");
+        }
+        Ok(CounterOutput {
+            state: pre_state + inp.delta,
+            next_predicate: inp.next_predicate.clone(),
+        })
+    }
+
+    fn extract_next_predicate(output: &Self::StepOutput) -> Option<PredicateKey> {
+        output.next_predicate.clone()
+    }
+
+    fn extract_post_state(output: &Self::StepOutput) -> &Self::State {
+        &output.state
+    }
+
+    fn compute_export_ops(_prev: &ExportState, output: &Self::StepOutput) -> Vec<ExportOp> {
+        vec![ExportOp::AddEntry {
+            container_id: COUNTER_CONTAINER_ID,
+            entry: counter_entry(output.state),
+        }]
+    }
+}
+
+/// Returns the export entry [`CounterProgram`] appends for the given counter value.
+pub(crate) fn counter_entry(counter: u64) -> [u8; 32] {
+    let mut entry = [0u8; 32];
+    entry[..8].copy_from_slice(&counter.to_le_bytes());
+    entry
+}
+
+/// Activation delay used by tests of delayed predicate updates.
+pub(crate) const PREDICATE_DELAY: u64 = 2;
+
+/// Creates the Moho state committing to the given counter value, produced under [`SPEC`].
+pub(crate) fn counter_moho_state(counter: u64) -> MohoState {
+    MohoState::new(
+        CounterProgram::compute_state_commitment(&counter),
+        always_accept(),
+        ExportState::new(vec![]).unwrap(),
+        CounterProgram::compute_spec_commitment(&SPEC),
+    )
+}
+
+/// Creates a [`RuntimeInput`] applying `input` on top of the given states.
+pub(crate) fn runtime_input(
+    moho_pre_state: MohoState,
+    counter: u64,
+    input: &CounterInput,
+) -> RuntimeInput {
+    RuntimeInput::new(moho_pre_state, counter.as_ssz_bytes(), input.as_ssz_bytes())
+}
@@ -1,2 +1,8 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod schedule;
 mod traits;
+pub use schedule::{SpecActivation, SpecSchedule};
 pub use traits::MohoProgram;
@@ -0,0 +1,104 @@
+//! Schedules of spec upgrades activated at predetermined points of a chain.
+
+use alloc::vec::Vec;
+
+use moho_types::{SpecCommitment, StateReference};
+
+use crate::MohoProgram;
+
+/// The point of a chain from which a scheduled spec is used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpecActivation {
+    /// Active from the first step input whose height, as reported by
+    /// [`MohoProgram::extract_height`], is at least the given height.
+    ///
+    /// Never reached by programs that do not report heights.
+    Height(u64),
+
+    /// Active from the step input producing the given reference, as computed by
+    /// [`MohoProgram::compute_input_reference`].
+    Reference(StateReference),
+}
+
+impl SpecActivation {
+    /// Returns whether the activation point is reached by the step input `inp`.
+    pub fn is_reached<P: MohoProgram>(&self, inp: &P::StepInput) -> bool {
+        match self {
+            Self::Height(height) => P::extract_height(inp).is_some_and(|h| h >= *height),
+            Self::Reference(reference) => P::compute_input_reference(inp) == *reference,
+        }
+    }
+}
+
+/// An ordered schedule of specs, each activated at a predetermined point of the chain.
+///
+/// The chain starts under the genesis spec. Each transition runs under the spec the pre-state
+/// committed to, unless the step input reaches the activation point of the spec scheduled after
+/// it, in which case it runs under the upgraded spec. Since the runtime binds the spec commitment
+/// into the post-state, later transitions keep running under the upgraded spec.
+///
+/// Specs in a schedule are identified by their [`SpecCommitment`], so they are expected to be
+/// distinct.
+#[derive(Clone, Debug)]
+pub struct SpecSchedule<S> {
+    /// The spec the chain starts under.
+    genesis: S,
+
+    /// The upgrades, in activation order.
+    upgrades: Vec<(SpecActivation, S)>,
+}
+
+impl<S> SpecSchedule<S> {
+    /// Creates a schedule running every transition under `genesis`.
+    pub const fn new(genesis: S) -> Self {
+        Self {
+            genesis,
+            upgrades: Vec::new(),
+        }
+    }
+
+    /// Schedules an upgrade to `spec` at `activation`, after all previously scheduled upgrades.
+    pub fn with_upgrade(mut self, activation: SpecActivation, spec: S) -> Self {
+        self.upgrades.push((activation, spec));
+        self
+    }
+
+    /// Returns the spec the chain starts under.
+    pub fn genesis(&self) -> &S {
+        &self.genesis
+    }
+
+    /// Returns the scheduled upgrades, in activation order.
+    pub fn upgrades(&self) -> impl Iterator<Item = (&SpecActivation, &S)> {
+        self.upgrades
+            .iter()
+            .map(|(activation, spec)| (activation, spec))
+    }
+
+    /// Returns all specs in the schedule, in activation order, starting with the genesis spec.
+    pub fn specs(&self) -> impl Iterator<Item = &S> {
+        core::iter::once(&self.genesis).chain(self.upgrades.iter().map(|(_, spec)| spec))
+    }
+
+    /// Selects the spec a step input applied to a pre-state committing to `current` runs under.
+    ///
+    /// Starting from the spec matching `current`, moves on to each following spec whose
+    /// activation point `inp` reaches.
+    ///
+    /// Returns `None` if no spec in the schedule matches `current`.
+    pub fn select<P: MohoProgram<Spec = S>>(
+        &self,
+        current: &SpecCommitment,
+        inp: &P::StepInput,
+    ) -> Option<&S> {
+        let mut idx = self
+            .specs()
+            .position(|spec| P::compute_spec_commitment(spec) == *current)?;
+        while let Some((activation, _)) = self.upgrades.get(idx)
+            && activation.is_reached::<P>(inp)
+        {
+            idx += 1;
+        }
+        self.specs().nth(idx)
+    }
+}
@@ -1,6 +1,9 @@
 //! Traits used to describe an inner state transition.
 
-use moho_types::{ExportState, InnerStateCommitment, StateReference};
+use alloc::vec::Vec;
+use core::fmt;
+
+use moho_types::{ExportOp, ExportState, InnerStateCommitment, SpecCommitment, StateReference};
 use ssz::{Decode, Encode};
 use strata_predicate::PredicateKey;
 
@@ -18,28 +21,75 @@ pub trait MohoProgram {
     /// Output after processing the step input
     type StepOutput;
 
+    /// Error returned when the program rejects a transition.
+    type Error: fmt::Debug + fmt::Display;
+
+    /// Number of further steps after which a predicate key returned by
+    /// [`extract_next_predicate`](Self::extract_next_predicate) becomes the next predicate.
+    ///
+    /// With a non-zero delay, the runtime records the key as the Moho state's pending predicate,
+    /// giving light clients and bridges notice of the upgrade before it takes effect. With the
+    /// default of `0`, the key replaces the next predicate immediately.
+    const PREDICATE_ACTIVATION_DELAY: u64 = 0;
+
     /// Computes the reference to the input state.
     fn compute_input_reference(input: &Self::StepInput) -> StateReference;
 
     /// Extracts the state reference to the input's previous input from it.
     fn extract_prev_reference(input: &Self::StepInput) -> StateReference;
 
+    /// Checks that the step input validly extends the inner pre-state (e.g. that a block's parent
+    /// hash matches the tip recorded in `pre_state`).
+    ///
+    /// Called by the runtime once `pre_state` has been checked against the Moho pre-state, before
+    /// the transition is processed. The default implementation accepts any linkage.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if `inp` does not extend `pre_state`.
+    fn check_reference_linkage(
+        pre_state: &Self::State,
+        inp: &Self::StepInput,
+    ) -> Result<(), Self::Error> {
+        let _ = (pre_state, inp);
+        Ok(())
+    }
+
+    /// Extracts the height of the step input, if the program has a notion of height.
+    ///
+    /// Used to evaluate [`SpecActivation::Height`](crate::SpecActivation::Height) activations.
+    /// The default implementation reports no height.
+    fn extract_height(input: &Self::StepInput) -> Option<u64> {
+        let _ = input;
+        None
+    }
+
     /// Computes the commitment to the inner state.
     fn compute_state_commitment(state: &Self::State) -> InnerStateCommitment;
 
+    /// Computes the commitment to a spec.
+    ///
+    /// The runtime binds the commitment to the spec a transition ran under into the post-state,
+    /// so that verifiers can detect steps executed under an unexpected spec.
+    fn compute_spec_commitment(spec: &Self::Spec) -> SpecCommitment;
+
     /// Computes the state transition from the input.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the provided `pre_state`, `spec`, and `inp` violate the program invariant.
+    /// Returns [`Self::Error`] if the provided `pre_state`, `spec`, and `inp` violate the program
+    /// invariant.
     fn process_transition(
         pre_state: &Self::State,
         spec: &Self::Spec,
         inp: &Self::StepInput,
-    ) -> Self::StepOutput;
+    ) -> Result<Self::StepOutput, Self::Error>;
 
     /// Extracts the next inner predicate key from a step’s output.
     ///
+    /// The key is activated after [`PREDICATE_ACTIVATION_DELAY`](Self::PREDICATE_ACTIVATION_DELAY)
+    /// further steps, replacing any predicate still pending.
+    ///
     /// # Returns
     ///
     /// - `Some(PredicateKey)` if the inner predicate key has been updated.
@@ -49,6 +99,10 @@ pub trait MohoProgram {
     /// Extracts the inner state after a transition from the step’s output.
     fn extract_post_state(output: &Self::StepOutput) -> &Self::State;
 
-    /// Computes the new exported state from the previous one and the step output.
-    fn compute_next_export_state(prev: ExportState, output: &Self::StepOutput) -> ExportState;
+    /// Computes the changes a step makes to the export state from the step output.
+    ///
+    /// The runtime applies the returned operations in order to `prev`, the export state of the
+    /// Moho pre-state. Operations can only create containers, append entries and update extra
+    /// data, so the resulting export state always extends `prev`.
+    fn compute_export_ops(prev: &ExportState, output: &Self::StepOutput) -> Vec<ExportOp>;
 }
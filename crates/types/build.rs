@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{fs, path::Path};
 
 use ssz_codegen::{ModuleGeneration, build_ssz_files};
 
@@ -22,5 +22,56 @@ fn main() {
     )
     .expect("Failed to generate SSZ types");
 
+    // Types whose SSZ decoding checks invariants implement `Decode` by hand.
+    let code = fs::read_to_string(&output_path).expect("Failed to read generated SSZ types");
+    let code = CHECKED_DECODE
+        .iter()
+        .fold(code, |code, ty| strip_decode_derive(&code, ty));
+    fs::write(&output_path, code).expect("Failed to write generated SSZ types");
+
     println!("cargo:rerun-if-changed=ssz/moho.ssz");
 }
+
+/// Generated types whose `Decode` impl is written by hand in the crate.
+const CHECKED_DECODE: &[&str] = &["ExportState"];
+
+/// Removes `Decode` from the derives of the generated struct `ty`.
+///
+/// Panics if the struct or its `Decode` derive cannot be found, so that a change to the generated
+/// code cannot silently bring back the unchecked impl.
+fn strip_decode_derive(code: &str, ty: &str) -> String {
+    let marker = format!("pub struct {ty}");
+    let start = code
+        .match_indices(&marker)
+        .map(|(index, _)| index)
+        .find(|index| {
+            code[index + marker.len()..].starts_with(|c: char| !c.is_alphanumeric() && c != '_')
+        })
+        .unwrap_or_else(|| panic!("generated SSZ types have no `{ty}` struct"));
+    let derive_start = code[..start]
+        .rfind("#[derive(")
+        .unwrap_or_else(|| panic!("generated `{ty}` has no derives"));
+    let list_start = derive_start + "#[derive(".len();
+    let list_end = list_start + code[list_start..].find(")]").expect("unterminated derive");
+
+    let derives: Vec<&str> = code[list_start..list_end]
+        .split(',')
+        .map(str::trim)
+        .collect();
+    let kept: Vec<&str> = derives
+        .iter()
+        .copied()
+        .filter(|d| !d.is_empty() && *d != "Decode" && !d.ends_with("::Decode"))
+        .collect();
+    assert!(
+        kept.len() + 1 == derives.iter().filter(|d| !d.is_empty()).count(),
+        "generated `{ty}` does not derive `Decode`"
+    );
+
+    format!(
+        "{}{}{}",
+        &code[..list_start],
+        kept.join(", "),
+        &code[list_end..]
+    )
+}
@@ -0,0 +1,131 @@
+//! Domain-separated claims verified by Moho proof predicates.
+//!
+//! Predicates verify a proof against raw claim bytes. To keep a proof of one claim from being
+//! accepted as proof of another — e.g. a step signature from one Moho deployment being replayed
+//! on another deployment sharing the same keys — every claim starts with a [`ClaimDomain`] that
+//! binds it to a [`ClaimKind`] and a [`MohoInstanceId`].
+
+use core::fmt;
+
+use ssz_derive::{Decode, Encode};
+
+use crate::{MohoInstanceId, StepMohoAttestation};
+
+/// The kind of claim a proof attests to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ClaimKind {
+    /// A single state transition, see [`StepMohoClaim`].
+    Step = 0,
+    /// An aggregated chain of state transitions.
+    Recursive = 1,
+}
+
+/// Domain separator prefixed to every claim verified by a Moho predicate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClaimDomain {
+    /// The [`ClaimKind`] tag.
+    tag: u8,
+
+    /// The Moho deployment the claim belongs to.
+    instance: MohoInstanceId,
+}
+
+impl ClaimDomain {
+    /// Creates the domain of claims of the given kind about `instance`.
+    pub fn new(kind: ClaimKind, instance: MohoInstanceId) -> Self {
+        Self {
+            tag: kind as u8,
+            instance,
+        }
+    }
+
+    /// Returns the raw [`ClaimKind`] tag.
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
+    /// Returns the Moho instance the claim belongs to.
+    pub fn instance(&self) -> &MohoInstanceId {
+        &self.instance
+    }
+}
+
+impl fmt::Display for ClaimDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.instance, self.tag)
+    }
+}
+
+/// The claim a step proof attests to: a [`StepMohoAttestation`] within the step domain of a Moho
+/// instance.
+///
+/// The SSZ encoding of this container is the exact claim passed to the step predicate.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StepMohoClaim {
+    /// The step domain of the instance the attestation belongs to.
+    domain: ClaimDomain,
+
+    /// The attested state transition.
+    attestation: StepMohoAttestation,
+}
+
+impl StepMohoClaim {
+    /// Creates the claim that `attestation` is a valid transition of `instance`.
+    pub fn new(instance: MohoInstanceId, attestation: StepMohoAttestation) -> Self {
+        Self {
+            domain: ClaimDomain::new(ClaimKind::Step, instance),
+            attestation,
+        }
+    }
+
+    /// Returns the step domain of the claim.
+    pub fn domain(&self) -> &ClaimDomain {
+        &self.domain
+    }
+
+    /// Returns the attested state transition.
+    pub fn attestation(&self) -> &StepMohoAttestation {
+        &self.attestation
+    }
+
+    /// Consumes self and returns the attested state transition.
+    pub fn into_attestation(self) -> StepMohoAttestation {
+        self.attestation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz::Encode;
+
+    use super::*;
+    use crate::{MohoStateCommitment, StateRefAttestation, StateReference};
+
+    fn step_attestation() -> StepMohoAttestation {
+        let state_ref = |byte| {
+            StateRefAttestation::new(
+                StateReference::new([byte; 32]),
+                MohoStateCommitment::new([byte; 32]),
+            )
+        };
+        StepMohoAttestation::new(state_ref(0), state_ref(1))
+    }
+
+    #[test]
+    fn step_claim_differs_across_instances() {
+        let a = StepMohoClaim::new(MohoInstanceId::new([1; 32]), step_attestation());
+        let b = StepMohoClaim::new(MohoInstanceId::new([2; 32]), step_attestation());
+        assert_ne!(a.as_ssz_bytes(), b.as_ssz_bytes());
+    }
+
+    #[test]
+    fn domain_differs_across_kinds() {
+        let instance = MohoInstanceId::new([1; 32]);
+        let step = ClaimDomain::new(ClaimKind::Step, instance);
+        let recursive = ClaimDomain::new(ClaimKind::Recursive, instance);
+        assert_ne!(step.as_ssz_bytes(), recursive.as_ssz_bytes());
+    }
+}
@@ -14,4 +14,35 @@ pub enum ExportStateError {
     /// failed
     #[error("Failed to add entry to container: {0}")]
     SszError(#[from] SszError),
+
+    /// No container with the given ID exists in the export state.
+    #[error("Export container {0} not found")]
+    ContainerNotFound(u8),
+
+    /// More than one container with the given ID exists in the export state.
+    #[error("Duplicate export container {0}")]
+    DuplicateContainerId(u8),
+
+    /// The containers of the export state are not sorted by ID.
+    #[error("Export containers are not sorted by ID")]
+    UnsortedContainers,
+
+    /// A container of the previous export state is missing from the next one.
+    #[error("Export container {0} was removed")]
+    ContainerRemoved(u8),
+
+    /// The MMR of a container has fewer entries than in the previous export state.
+    #[error("Export container {container_id} MMR shrank from {prev_entries} to {entries} entries")]
+    MmrShrunk {
+        /// ID of the container.
+        container_id: u8,
+        /// Number of entries in the previous export state.
+        prev_entries: u64,
+        /// Number of entries in the next export state.
+        entries: u64,
+    },
+
+    /// The MMR of a container differs from the previous export state without having grown.
+    #[error("Export container {0} MMR was rewritten")]
+    MmrRewritten(u8),
 }
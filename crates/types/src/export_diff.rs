@@ -0,0 +1,239 @@
+//! Changes between two versions of an [`ExportState`].
+//!
+//! Export containers are append-only: containers may be created but never removed, and their
+//! MMRs only grow. [`ExportState::diff`] reports what a state transition exported — created
+//! containers, appended entries and extra data updates — and rejects changes violating these
+//! rules.
+//!
+//! Containers only store MMR peaks, so the diff can tell how many entries were appended but not
+//! which ones, nor whether the new MMR extends the previous one rather than replacing it with a
+//! different history of greater length. Only an MMR rewritten at the same size is detected.
+
+use alloc::vec::Vec;
+
+use crate::{ExportContainer, ExportState, errors::ExportStateError};
+
+type Hash32 = [u8; 32];
+
+/// The changes to a single export container between two versions of an [`ExportState`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContainerDiff {
+    /// ID of the container.
+    container_id: u8,
+
+    /// Whether the container did not exist in the previous version.
+    created: bool,
+
+    /// Number of entries appended to the container's MMR.
+    appended: u64,
+
+    /// The new extra data, if it changed.
+    extra_data: Option<Hash32>,
+}
+
+impl ContainerDiff {
+    /// Returns the ID of the container.
+    pub fn container_id(&self) -> u8 {
+        self.container_id
+    }
+
+    /// Returns whether the container did not exist in the previous version.
+    pub fn created(&self) -> bool {
+        self.created
+    }
+
+    /// Returns the number of entries appended to the container's MMR.
+    pub fn appended(&self) -> u64 {
+        self.appended
+    }
+
+    /// Returns the new extra data, if it changed.
+    ///
+    /// A created container reports its extra data if it is not zeroed out.
+    pub fn extra_data(&self) -> Option<&Hash32> {
+        self.extra_data.as_ref()
+    }
+}
+
+/// The changes between two versions of an [`ExportState`], as returned by
+/// [`ExportState::diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExportDiff {
+    /// The containers that changed, sorted by ID.
+    containers: Vec<ContainerDiff>,
+}
+
+impl ExportDiff {
+    /// Returns the containers that changed, sorted by ID.
+    pub fn containers(&self) -> &[ContainerDiff] {
+        &self.containers
+    }
+
+    /// Returns the changes to the container with the specified ID, if it changed.
+    pub fn container(&self, container_id: u8) -> Option<&ContainerDiff> {
+        self.containers
+            .binary_search_by_key(&container_id, |c| c.container_id)
+            .ok()
+            .map(|index| &self.containers[index])
+    }
+
+    /// Returns whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    /// Returns the total number of entries appended across all containers.
+    pub fn appended(&self) -> u64 {
+        self.containers.iter().map(|c| c.appended).sum()
+    }
+}
+
+impl ExportState {
+    /// Computes the changes from `prev` to this export state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ExportStateError`] if either export state is invalid, if a container of
+    /// `prev` was removed, or if a container's MMR shrank or was rewritten.
+    pub fn diff(&self, prev: &ExportState) -> Result<ExportDiff, ExportStateError> {
+        prev.validate()?;
+        self.validate()?;
+
+        if let Some(removed) = prev
+            .containers()
+            .iter()
+            .find(|c| self.get_container(c.container_id()).is_none())
+        {
+            return Err(ExportStateError::ContainerRemoved(removed.container_id()));
+        }
+
+        let mut containers = Vec::new();
+        for container in self.containers() {
+            let diff = match prev.get_container(container.container_id()) {
+                Some(prev_container) => container_diff(prev_container, container)?,
+                None => ContainerDiff {
+                    container_id: container.container_id(),
+                    created: true,
+                    appended: container.entries_mmr().num_entries(),
+                    extra_data: Some(*container.extra_data())
+                        .filter(|extra_data| *extra_data != Hash32::default()),
+                },
+            };
+            if diff.created || diff.appended > 0 || diff.extra_data.is_some() {
+                containers.push(diff);
+            }
+        }
+        Ok(ExportDiff { containers })
+    }
+}
+
+/// Computes the changes from `prev` to `next`, two versions of the same container.
+fn container_diff(
+    prev: &ExportContainer,
+    next: &ExportContainer,
+) -> Result<ContainerDiff, ExportStateError> {
+    let container_id = next.container_id();
+    let prev_entries = prev.entries_mmr().num_entries();
+    let entries = next.entries_mmr().num_entries();
+    if entries < prev_entries {
+        return Err(ExportStateError::MmrShrunk {
+            container_id,
+            prev_entries,
+            entries,
+        });
+    }
+    if entries == prev_entries && next.entries_mmr() != prev.entries_mmr() {
+        return Err(ExportStateError::MmrRewritten(container_id));
+    }
+
+    Ok(ContainerDiff {
+        container_id,
+        created: false,
+        appended: entries - prev_entries,
+        extra_data: Some(*next.extra_data()).filter(|extra_data| extra_data != prev.extra_data()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(containers: &[(u8, &[Hash32])]) -> ExportState {
+        let mut state = ExportState::new(vec![]).unwrap();
+        for (id, entries) in containers {
+            state.update_extra_data(*id, Hash32::default());
+            for entry in *entries {
+                state.add_entry(*id, *entry).unwrap();
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn test_diff_reports_changes() {
+        let prev = state(&[(1, &[[0x11; 32]]), (2, &[[0x21; 32]]), (3, &[])]);
+        let mut next = prev.clone();
+        next.add_entry(1, [0x12; 32]).unwrap();
+        next.add_entry(1, [0x13; 32]).unwrap();
+        next.update_extra_data(2, [0xEE; 32]);
+        next.add_entry(5, [0x51; 32]).unwrap();
+
+        let diff = next.diff(&prev).unwrap();
+        let ids: Vec<u8> = diff.containers().iter().map(|c| c.container_id()).collect();
+        assert_eq!(ids, [1, 2, 5]);
+        assert_eq!(diff.appended(), 3);
+
+        let first = diff.container(1).unwrap();
+        assert!(!first.created());
+        assert_eq!(first.appended(), 2);
+        assert_eq!(first.extra_data(), None);
+
+        let second = diff.container(2).unwrap();
+        assert_eq!(second.appended(), 0);
+        assert_eq!(second.extra_data(), Some(&[0xEE; 32]));
+
+        let created = diff.container(5).unwrap();
+        assert!(created.created());
+        assert_eq!(created.appended(), 1);
+        assert_eq!(created.extra_data(), None);
+
+        // Unchanged containers are not reported.
+        assert!(diff.container(3).is_none());
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let prev = state(&[(1, &[[0x11; 32]])]);
+        let diff = prev.diff(&prev).unwrap();
+        assert!(diff.is_empty());
+        assert_eq!(diff, ExportDiff::default());
+    }
+
+    #[test]
+    fn test_diff_rejects_removed_container() {
+        let prev = state(&[(1, &[]), (2, &[])]);
+        let next = state(&[(2, &[])]);
+        assert_eq!(next.diff(&prev), Err(ExportStateError::ContainerRemoved(1)));
+    }
+
+    #[test]
+    fn test_diff_rejects_shrunk_mmr() {
+        let prev = state(&[(1, &[[0x11; 32], [0x12; 32]])]);
+        let next = state(&[(1, &[[0x11; 32]])]);
+        assert_eq!(
+            next.diff(&prev),
+            Err(ExportStateError::MmrShrunk {
+                container_id: 1,
+                prev_entries: 2,
+                entries: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_diff_rejects_rewritten_mmr() {
+        let prev = state(&[(1, &[[0x11; 32]])]);
+        let next = state(&[(1, &[[0x99; 32]])]);
+        assert_eq!(next.diff(&prev), Err(ExportStateError::MmrRewritten(1)));
+    }
+}
@@ -0,0 +1,208 @@
+//! Typed handles to the export containers of an [`ExportState`].
+//!
+//! Export containers are identified by a raw `u8` and store raw 32-byte entry hashes and extra
+//! data. Applications instead describe their container with an [`ExportContainerKind`], which
+//! fixes its ID at compile time along with the entry type appended to it and the type of its
+//! extra data, and access it through the typed accessors of [`ExportState`] and
+//! [`ExportEntryProof`].
+
+use crate::{
+    ExportContainer, ExportEntryProof, ExportState, MohoStateCommitment, errors::ExportStateError,
+};
+
+type Hash32 = [u8; 32];
+
+/// An entry that can be appended to an export container.
+pub trait ExportEntry {
+    /// Computes the canonical hash of the entry, which is what the container's MMR commits to.
+    fn entry_hash(&self) -> Hash32;
+}
+
+/// Raw entry hashes are committed as is.
+impl ExportEntry for Hash32 {
+    fn entry_hash(&self) -> Hash32 {
+        *self
+    }
+}
+
+/// Data stored in the 32-byte `extra_data` of an export container.
+pub trait ExportExtraData: Sized {
+    /// Decodes the extra data from its raw container representation.
+    fn from_extra_data(raw: &Hash32) -> Self;
+
+    /// Encodes the extra data into its raw container representation.
+    fn to_extra_data(&self) -> Hash32;
+}
+
+/// Raw extra data is stored as is.
+impl ExportExtraData for Hash32 {
+    fn from_extra_data(raw: &Hash32) -> Self {
+        *raw
+    }
+
+    fn to_extra_data(&self) -> Hash32 {
+        *self
+    }
+}
+
+/// An application exporting data through a dedicated export container.
+///
+/// Each application, e.g. a bridge design, implements this trait on a marker type. Container IDs
+/// must be unique across the applications of a Moho deployment.
+pub trait ExportContainerKind {
+    /// The ID of the application's export container.
+    const ID: u8;
+
+    /// The type of the entries appended to the container.
+    type Entry: ExportEntry;
+
+    /// The type of the container's extra data.
+    type ExtraData: ExportExtraData;
+}
+
+impl ExportState {
+    /// Returns the container of kind `K`, if it exists.
+    pub fn container_of<K: ExportContainerKind>(&self) -> Option<&ExportContainer> {
+        self.get_container(K::ID)
+    }
+
+    /// Appends `entry` to the container of kind `K`, creating the container if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportStateError::AddEntryFailed` if the MMR capacity is exceeded.
+    pub fn add_entry_of<K: ExportContainerKind>(
+        &mut self,
+        entry: &K::Entry,
+    ) -> Result<(), ExportStateError> {
+        self.add_entry(K::ID, entry.entry_hash())
+    }
+
+    /// Returns the extra data of the container of kind `K`, if the container exists.
+    pub fn extra_data_of<K: ExportContainerKind>(&self) -> Option<K::ExtraData> {
+        self.container_of::<K>()
+            .map(|c| K::ExtraData::from_extra_data(c.extra_data()))
+    }
+
+    /// Updates the extra data of the container of kind `K`, creating the container if needed.
+    pub fn update_extra_data_of<K: ExportContainerKind>(&mut self, extra_data: &K::ExtraData) {
+        self.update_extra_data(K::ID, extra_data.to_extra_data());
+    }
+}
+
+impl ExportEntryProof {
+    /// Verifies that `entry` is included in the container of kind `K` of the export state of the
+    /// Moho state committed to by `commitment`.
+    pub fn verify_entry_of<K: ExportContainerKind>(
+        &self,
+        commitment: &MohoStateCommitment,
+        entry: &K::Entry,
+    ) -> bool {
+        self.verify(commitment, K::ID, &entry.entry_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    /// A withdrawal exported by [`Bridge`].
+    struct Withdrawal {
+        amount: u64,
+    }
+
+    impl ExportEntry for Withdrawal {
+        fn entry_hash(&self) -> Hash32 {
+            Sha256::digest(self.amount.to_le_bytes()).into()
+        }
+    }
+
+    /// The total amount withdrawn through [`Bridge`].
+    #[derive(Debug, PartialEq)]
+    struct Total(u64);
+
+    impl ExportExtraData for Total {
+        fn from_extra_data(raw: &Hash32) -> Self {
+            Total(u64::from_le_bytes(raw[..8].try_into().unwrap()))
+        }
+
+        fn to_extra_data(&self) -> Hash32 {
+            let mut raw = Hash32::default();
+            raw[..8].copy_from_slice(&self.0.to_le_bytes());
+            raw
+        }
+    }
+
+    struct Bridge;
+
+    impl ExportContainerKind for Bridge {
+        const ID: u8 = 3;
+        type Entry = Withdrawal;
+        type ExtraData = Total;
+    }
+
+    struct Raw;
+
+    impl ExportContainerKind for Raw {
+        const ID: u8 = 9;
+        type Entry = Hash32;
+        type ExtraData = Hash32;
+    }
+
+    #[test]
+    fn test_typed_entries() {
+        let mut state = ExportState::new(vec![]).unwrap();
+        assert!(state.container_of::<Bridge>().is_none());
+        assert!(state.extra_data_of::<Bridge>().is_none());
+
+        state
+            .add_entry_of::<Bridge>(&Withdrawal { amount: 5 })
+            .unwrap();
+        state.add_entry_of::<Raw>(&[0xAA; 32]).unwrap();
+        state
+            .add_entry_of::<Bridge>(&Withdrawal { amount: 7 })
+            .unwrap();
+
+        let bridge = state.container_of::<Bridge>().expect("container created");
+        assert_eq!(bridge.container_id(), Bridge::ID);
+        assert_eq!(bridge.entries_mmr().num_entries(), 2);
+        assert_eq!(
+            state
+                .container_of::<Raw>()
+                .unwrap()
+                .entries_mmr()
+                .num_entries(),
+            1
+        );
+
+        // Typed entries are committed via their canonical hash.
+        let mut raw = ExportState::new(vec![]).unwrap();
+        raw.add_entry(Bridge::ID, Withdrawal { amount: 5 }.entry_hash())
+            .unwrap();
+        raw.add_entry(Bridge::ID, Withdrawal { amount: 7 }.entry_hash())
+            .unwrap();
+        assert_eq!(raw.container_of::<Bridge>(), Some(bridge));
+    }
+
+    #[test]
+    fn test_typed_extra_data() {
+        let mut state = ExportState::new(vec![]).unwrap();
+        state.update_extra_data_of::<Bridge>(&Total(12));
+
+        assert_eq!(state.extra_data_of::<Bridge>(), Some(Total(12)));
+        assert_eq!(
+            state.container_of::<Bridge>().unwrap().extra_data()[..8],
+            12u64.to_le_bytes()
+        );
+        assert_eq!(
+            state
+                .container_of::<Bridge>()
+                .unwrap()
+                .entries_mmr()
+                .num_entries(),
+            0
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Append-only operations on an [`ExportState`].
+//!
+//! State transitions do not produce their export state directly but describe the changes they
+//! make to it as a list of [`ExportOp`]s, which can only create containers, append entries to
+//! their MMRs and update their extra data. Applying them to an export state with
+//! [`ExportState::apply`] therefore always yields an extension of it: exported entries are never
+//! retracted.
+
+use crate::{
+    ExportContainerKind, ExportEntry, ExportExtraData, ExportState, errors::ExportStateError,
+};
+
+type Hash32 = [u8; 32];
+
+/// A single append-only change to an [`ExportState`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportOp {
+    /// Appends an entry to a container's MMR, creating the container if needed.
+    AddEntry {
+        /// ID of the container.
+        container_id: u8,
+        /// The entry hash to append.
+        entry: Hash32,
+    },
+
+    /// Updates a container's extra data, creating the container if needed.
+    UpdateExtraData {
+        /// ID of the container.
+        container_id: u8,
+        /// The new extra data.
+        extra_data: Hash32,
+    },
+}
+
+impl ExportOp {
+    /// Returns the operation appending `entry` to the container of kind `K`.
+    pub fn add_entry_of<K: ExportContainerKind>(entry: &K::Entry) -> Self {
+        Self::AddEntry {
+            container_id: K::ID,
+            entry: entry.entry_hash(),
+        }
+    }
+
+    /// Returns the operation updating the extra data of the container of kind `K`.
+    pub fn update_extra_data_of<K: ExportContainerKind>(extra_data: &K::ExtraData) -> Self {
+        Self::UpdateExtraData {
+            container_id: K::ID,
+            extra_data: extra_data.to_extra_data(),
+        }
+    }
+
+    /// Returns the ID of the container the operation changes.
+    pub fn container_id(&self) -> u8 {
+        match self {
+            Self::AddEntry { container_id, .. } | Self::UpdateExtraData { container_id, .. } => {
+                *container_id
+            }
+        }
+    }
+}
+
+impl ExportState {
+    /// Applies `op` to the export state.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportStateError::AddEntryFailed` if the MMR capacity is exceeded.
+    pub fn apply(&mut self, op: &ExportOp) -> Result<(), ExportStateError> {
+        match *op {
+            ExportOp::AddEntry {
+                container_id,
+                entry,
+            } => self.add_entry(container_id, entry),
+            ExportOp::UpdateExtraData {
+                container_id,
+                extra_data,
+            } => {
+                self.update_extra_data(container_id, extra_data);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_extends_export_state() {
+        let mut prev = ExportState::new(vec![]).unwrap();
+        prev.add_entry(1, [0x11; 32]).unwrap();
+
+        let ops = [
+            ExportOp::AddEntry {
+                container_id: 1,
+                entry: [0x12; 32],
+            },
+            ExportOp::UpdateExtraData {
+                container_id: 2,
+                extra_data: [0xEE; 32],
+            },
+            ExportOp::AddEntry {
+                container_id: 2,
+                entry: [0x21; 32],
+            },
+        ];
+        let mut next = prev.clone();
+        for op in &ops {
+            next.apply(op).unwrap();
+        }
+
+        let mut expected = prev.clone();
+        expected.add_entry(1, [0x12; 32]).unwrap();
+        expected.update_extra_data(2, [0xEE; 32]);
+        expected.add_entry(2, [0x21; 32]).unwrap();
+        assert_eq!(next, expected);
+
+        let diff = next.diff(&prev).unwrap();
+        assert_eq!(diff.appended(), 2);
+        assert!(diff.container(2).unwrap().created());
+        assert_eq!(ops.map(|op| op.container_id()), [1, 2, 2]);
+    }
+}
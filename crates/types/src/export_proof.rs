@@ -0,0 +1,308 @@
+//! Inclusion proofs for export entries against a [`MohoStateCommitment`].
+//!
+//! An export entry is committed to by three nested accumulators:
+//!
+//! 1. the `entries_mmr` of its [`ExportContainer`],
+//! 2. the `containers` list of the [`ExportState`],
+//! 3. the `export_state` field of the [`MohoState`].
+//!
+//! [`ExportEntryProof`] bundles one proof per level so that a consumer holding only a
+//! [`MohoStateCommitment`] (e.g. from a verified recursive Moho proof) can check that an entry was
+//! exported.
+
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+use ssz_derive::{Decode, Encode};
+use ssz_types::FixedBytes;
+use strata_merkle::{
+    BinaryMerkleTree, MerkleProofB32, Mmr, Sha256Hasher as MerkleHasher, Sha256NoPrefixHasher,
+};
+use tree_hash::{Sha256Hasher, TreeHash};
+
+use crate::{
+    ExportContainer, ExportState, MohoState, MohoStateCommitment, MohoStateField,
+    errors::ExportStateError, state::MAX_EXPORT_CONTAINERS,
+};
+
+type Hash32 = [u8; 32];
+
+/// Depth of the Merkle tree over the container list of an [`ExportState`], i.e. the number of
+/// cohashes in a container proof.
+const CONTAINER_TREE_DEPTH: usize = MAX_EXPORT_CONTAINERS.trailing_zeros() as usize;
+
+const _: () = assert!(MAX_EXPORT_CONTAINERS.is_power_of_two());
+
+/// Proof that an entry is included in an [`ExportContainer`] of the [`ExportState`] of the
+/// [`MohoState`] committed to by a [`MohoStateCommitment`].
+#[derive(Clone, Debug, Encode, Decode)]
+pub struct ExportEntryProof {
+    /// The container holding the entry. Its MMR peaks are what the entry proof is checked
+    /// against.
+    container: ExportContainer,
+
+    /// MMR inclusion proof of the entry within the container's `entries_mmr`.
+    entry_proof: MerkleProofB32,
+
+    /// Merkle proof of the container within the data tree of the export state's container list.
+    container_proof: MerkleProofB32,
+
+    /// Root of the data tree of the export state's container list, before mixing in the length.
+    containers_root: FixedBytes<32>,
+
+    /// Number of containers in the export state, mixed into the container list root.
+    container_count: u64,
+
+    /// Merkle proof of the export state within the Moho state.
+    export_state_proof: MerkleProofB32,
+}
+
+impl ExportEntryProof {
+    /// Builds a proof for an entry of container `container_id` of `state`'s export state.
+    ///
+    /// `entry_proof` is the MMR inclusion proof of the entry against that container's current
+    /// `entries_mmr`. Containers only store MMR peaks, so this must be obtained from whoever
+    /// retained the leaves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExportStateError::ContainerNotFound`] if the export state has no container with
+    /// the given ID.
+    pub fn new(
+        state: &MohoState,
+        container_id: u8,
+        entry_proof: MerkleProofB32,
+    ) -> Result<Self, ExportStateError> {
+        let position = state
+            .export_state()
+            .position(container_id)
+            .map_err(|_| ExportStateError::ContainerNotFound(container_id))?;
+        let containers = state.export_state().containers();
+
+        let mut leaves: Vec<Hash32> = containers
+            .iter()
+            .map(|c| <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(c).into_inner())
+            .collect();
+        leaves.resize(MAX_EXPORT_CONTAINERS, Hash32::default());
+        let tree = BinaryMerkleTree::from_leaves::<Sha256NoPrefixHasher>(leaves)
+            .expect("export proof: container tree is a power of two");
+        let container_proof = tree
+            .gen_proof(position)
+            .expect("export proof: container index within tree");
+
+        Ok(Self {
+            container: containers[position].clone(),
+            entry_proof,
+            container_proof: MerkleProofB32::from_generic(&container_proof),
+            containers_root: FixedBytes(*tree.root()),
+            container_count: containers.len() as u64,
+            export_state_proof: state.prove_export_state(),
+        })
+    }
+
+    /// Returns the container holding the entry.
+    pub fn container(&self) -> &ExportContainer {
+        &self.container
+    }
+
+    /// Returns the MMR inclusion proof of the entry within the container.
+    pub fn entry_proof(&self) -> &MerkleProofB32 {
+        &self.entry_proof
+    }
+
+    /// Verifies that `entry` is included in container `container_id` of the export state of the
+    /// Moho state committed to by `commitment`.
+    pub fn verify(
+        &self,
+        commitment: &MohoStateCommitment,
+        container_id: u8,
+        entry: &Hash32,
+    ) -> bool {
+        // 1: The entry is a leaf of the container's MMR.
+        if self.container.container_id() != container_id
+            || !Mmr::<MerkleHasher>::verify(
+                self.container.entries_mmr(),
+                &self.entry_proof.to_generic(),
+                entry,
+            )
+        {
+            return false;
+        }
+
+        // 2: The container is an element of the export state's container list.
+        let container_root =
+            <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(&self.container).into_inner();
+        if self.container_proof.index >= self.container_count
+            || self.container_proof.cohashes.len() != CONTAINER_TREE_DEPTH
+            || !self
+                .container_proof
+                .verify_with_root::<Sha256NoPrefixHasher>(&self.containers_root.0, &container_root)
+        {
+            return false;
+        }
+
+        // 3: The export state is the `export_state` field of the Moho state. `ExportState` has a
+        // single field, so its root is the root of the container list. `verify_field` checks the
+        // depth of the proof.
+        let export_state_root = mix_in_length(&self.containers_root.0, self.container_count);
+        MohoState::verify_field(
+            commitment,
+            MohoStateField::ExportState,
+            &export_state_root,
+            &self.export_state_proof,
+        )
+    }
+}
+
+/// Mixes the length of an SSZ list into the root of its data tree.
+fn mix_in_length(root: &Hash32, len: u64) -> Hash32 {
+    let mut length = Hash32::default();
+    length[..8].copy_from_slice(&len.to_le_bytes());
+
+    let mut hasher = Sha256::new();
+    hasher.update(root);
+    hasher.update(length);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz_types::VariableList;
+    use strata_merkle::{MerkleProof, MmrState};
+
+    use super::*;
+    use crate::{InnerStateCommitment, SpecCommitment, test_utils::always_accept};
+
+    /// Builds a container from `entries`, returning it along with the MMR proof of every entry.
+    fn container_with_proofs(
+        container_id: u8,
+        entries: &[Hash32],
+    ) -> (ExportContainer, Vec<MerkleProof<Hash32>>) {
+        let mut container = ExportContainer::new(container_id);
+        let mut proofs = Vec::new();
+        for entry in entries {
+            let proof = Mmr::<MerkleHasher>::add_leaf_updating_proof_list(
+                &mut container.entries_mmr,
+                *entry,
+                &mut proofs,
+            )
+            .unwrap();
+            proofs.push(proof);
+        }
+        (container, proofs)
+    }
+
+    fn sample_state(containers: Vec<ExportContainer>) -> MohoState {
+        MohoState::new(
+            InnerStateCommitment::from([0x11; 32]),
+            always_accept(),
+            ExportState::new(containers).unwrap(),
+            SpecCommitment::default(),
+        )
+    }
+
+    #[test]
+    fn test_mix_in_length_matches_tree_hash() {
+        let (container1, _) = container_with_proofs(1, &[[1; 32], [2; 32]]);
+        let (container2, _) = container_with_proofs(2, &[[3; 32]]);
+        let export_state = ExportState::new(vec![container1, container2]).unwrap();
+
+        let mut leaves: Vec<Hash32> = export_state
+            .containers()
+            .iter()
+            .map(|c| <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(c).into_inner())
+            .collect();
+        leaves.resize(MAX_EXPORT_CONTAINERS, Hash32::default());
+        let tree = BinaryMerkleTree::from_leaves::<Sha256NoPrefixHasher>(leaves).unwrap();
+
+        assert_eq!(
+            mix_in_length(tree.root(), 2),
+            <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(&export_state).into_inner()
+        );
+    }
+
+    #[test]
+    fn test_entry_proof_verifies() {
+        let entries = [[0xA1; 32], [0xA2; 32], [0xA3; 32]];
+        let (bridge, proofs) = container_with_proofs(1, &entries);
+        let (other, _) = container_with_proofs(9, &[[0xB1; 32]]);
+        assert_eq!(bridge.entries_mmr().num_entries(), 3);
+
+        let state = sample_state(vec![other, bridge]);
+        let commitment = state.compute_commitment();
+
+        for (entry, proof) in entries.iter().zip(&proofs) {
+            let proof = ExportEntryProof::new(&state, 1, MerkleProofB32::from_generic(proof))
+                .expect("container exists");
+            assert!(proof.verify(&commitment, 1, entry));
+        }
+    }
+
+    #[test]
+    fn test_entry_proof_rejects_mismatches() {
+        let entries = [[0xA1; 32], [0xA2; 32]];
+        let (bridge, proofs) = container_with_proofs(1, &entries);
+        let state = sample_state(vec![bridge]);
+        let commitment = state.compute_commitment();
+
+        let proof = ExportEntryProof::new(&state, 1, MerkleProofB32::from_generic(&proofs[0]))
+            .expect("container exists");
+
+        assert!(proof.verify(&commitment, 1, &entries[0]));
+        assert!(!proof.verify(&commitment, 1, &entries[1]), "wrong entry");
+        assert!(
+            !proof.verify(&commitment, 2, &entries[0]),
+            "wrong container"
+        );
+
+        let other_state = sample_state(vec![ExportContainer::new(1)]);
+        assert!(
+            !proof.verify(&other_state.compute_commitment(), 1, &entries[0]),
+            "wrong commitment"
+        );
+    }
+
+    #[test]
+    fn test_entry_proof_rejects_wrong_depth() {
+        let entries = [[0xA1; 32]];
+        let (bridge, proofs) = container_with_proofs(1, &entries);
+        let state = sample_state(vec![bridge]);
+        let commitment = state.compute_commitment();
+        let proof = ExportEntryProof::new(&state, 1, MerkleProofB32::from_generic(&proofs[0]))
+            .expect("container exists");
+        assert_eq!(proof.container_proof.cohashes.len(), CONTAINER_TREE_DEPTH);
+        assert_eq!(
+            proof.export_state_proof.cohashes.len(),
+            MohoStateField::TREE_DEPTH
+        );
+
+        let mut short = proof.clone();
+        short.container_proof.cohashes =
+            VariableList::new(proof.container_proof.cohashes[1..].to_vec()).unwrap();
+        short.container_proof.index /= 2;
+        assert!(
+            !short.verify(&commitment, 1, &entries[0]),
+            "short container proof"
+        );
+
+        let mut long = proof.clone();
+        let mut cohashes = proof.export_state_proof.cohashes.to_vec();
+        cohashes.push(FixedBytes([0; 32]));
+        long.export_state_proof.cohashes = VariableList::new(cohashes).unwrap();
+        assert!(
+            !long.verify(&commitment, 1, &entries[0]),
+            "long export state proof"
+        );
+    }
+
+    #[test]
+    fn test_entry_proof_missing_container() {
+        let (bridge, proofs) = container_with_proofs(1, &[[0xA1; 32]]);
+        let state = sample_state(vec![bridge]);
+        let proof = MerkleProofB32::from_generic(&proofs[0]);
+        assert!(matches!(
+            ExportEntryProof::new(&state, 2, proof),
+            Err(ExportStateError::ContainerNotFound(2))
+        ));
+    }
+}
@@ -60,3 +60,5 @@ macro_rules! inst_id {
 inst_id!(StateReference);
 inst_id!(InnerStateCommitment);
 inst_id!(MohoStateCommitment);
+inst_id!(MohoInstanceId);
+inst_id!(SpecCommitment);
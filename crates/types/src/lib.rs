@@ -1,18 +1,37 @@
 //! moho types
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod claim;
 pub mod errors;
+mod export_diff;
+mod export_kind;
+mod export_op;
+mod export_proof;
 mod id;
 mod relation;
 #[cfg(feature = "serde")]
 mod serde;
 mod state;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
+pub use claim::{ClaimDomain, ClaimKind, StepMohoClaim};
 pub use errors::ExportStateError;
-pub use id::{InnerStateCommitment, MohoStateCommitment, StateReference};
+pub use export_diff::{ContainerDiff, ExportDiff};
+pub use export_kind::{ExportContainerKind, ExportEntry, ExportExtraData};
+pub use export_op::ExportOp;
+pub use export_proof::ExportEntryProof;
+pub use id::{
+    InnerStateCommitment, MohoInstanceId, MohoStateCommitment, SpecCommitment, StateReference,
+};
 pub use relation::{
     ChainError, RecursiveMohoAttestation, RecursiveMohoProof, StateRefAttestation,
     StepMohoAttestation, StepMohoProof,
 };
+pub use state::MohoStateField;
 
 // Include generated SSZ types from build.rs output
 #[allow(
@@ -26,4 +45,4 @@ mod ssz_generated {
 }
 
 // Publicly re-export only the SSZ items this crate's API intends to expose
-pub use ssz_generated::ssz::moho::{ExportContainer, ExportState, MohoState};
+pub use ssz_generated::ssz::moho::{ExportContainer, ExportState, MohoState, PendingPredicate};
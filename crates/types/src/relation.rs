@@ -10,24 +10,32 @@
 //! proof, then check that they are continuous — i.e., the proven state of the recursive
 //! attestation matches the starting state of the step attestation. If so, the recursive
 //! attestation advances to the step's target state while preserving the original genesis.
+//!
+//! Each recursive attestation also counts the steps it covers, so that two attestations can be
+//! compared by progress.
+//!
+//! Two recursive attestations covering adjacent ranges can likewise be merged, which allows many
+//! step proofs to be aggregated in parallel as a binary tree rather than strictly sequentially.
 
-use std::fmt;
+use core::fmt;
 
 use ssz_derive::{Decode, Encode};
 use thiserror::Error;
 
 use crate::{MohoStateCommitment, StateReference};
 
-/// Error returned by [`RecursiveMohoAttestation::chain`] when the recursive attestation's proven
-/// state does not match the step attestation's starting state.
+/// Error returned by [`RecursiveMohoAttestation::chain`] and [`RecursiveMohoAttestation::merge`]
+/// when the recursive attestation's proven state does not match the starting state of the
+/// attestation appended to it.
 #[derive(Debug, Clone, Error)]
 #[error(
-    "cannot chain attestations: recursive proof ends at {recursive_end}, but step proof starts at {step_start}"
+    "cannot chain attestations: recursive proof ends at {recursive_end}, but appended proof starts at {step_start}"
 )]
 pub struct ChainError {
     /// The proven state of the recursive attestation.
     pub recursive_end: StateRefAttestation,
-    /// The starting state of the step attestation.
+    /// The starting state of the appended attestation: the step's starting state when chaining,
+    /// or the genesis of the right-hand recursive attestation when merging.
     pub step_start: StateRefAttestation,
 }
 
@@ -36,7 +44,7 @@ pub struct ChainError {
 ///
 /// Created by recursively chaining [`StepMohoAttestation`]s: each call to [`chain`](Self::chain)
 /// verifies continuity between the current proven state and the step's starting state, then
-/// advances the proven state to the step's target.
+/// advances the proven state to the step's target and increments the step count.
 #[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecursiveMohoAttestation {
@@ -45,11 +53,18 @@ pub struct RecursiveMohoAttestation {
 
     /// The most recent state proven as reachable from genesis through a sequence of valid steps.
     proven: StateRefAttestation,
+
+    /// The number of steps between genesis and the proven state.
+    steps: u64,
 }
 
 impl RecursiveMohoAttestation {
-    pub fn new(genesis: StateRefAttestation, proven: StateRefAttestation) -> Self {
-        Self { genesis, proven }
+    pub fn new(genesis: StateRefAttestation, proven: StateRefAttestation, steps: u64) -> Self {
+        Self {
+            genesis,
+            proven,
+            steps,
+        }
     }
 
     pub fn genesis(&self) -> &StateRefAttestation {
@@ -60,12 +75,20 @@ impl RecursiveMohoAttestation {
         &self.proven
     }
 
+    /// Returns the number of steps between genesis and the proven state.
+    ///
+    /// The count only grows as the attestation is extended, so of two attestations sharing a
+    /// genesis, the one with more steps is the more advanced.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
     /// Extends this recursive attestation with a step attestation, producing a new recursive
     /// attestation that covers the combined range.
     ///
     /// Succeeds only if the attestations are continuous — i.e., the current proven state matches
     /// the step's starting state. The resulting attestation retains the same genesis but advances
-    /// the proven state to the step's target.
+    /// the proven state to the step's target and covers one more step.
     ///
     /// Returns a [`ChainError`] carrying the mismatched endpoints if there is a gap between the
     /// two attestations.
@@ -78,7 +101,11 @@ impl RecursiveMohoAttestation {
         step: StepMohoAttestation,
     ) -> Result<Self, ChainError> {
         if self.proven() == step.from() {
-            Ok(RecursiveMohoAttestation::new(self.genesis, step.to))
+            Ok(RecursiveMohoAttestation::new(
+                self.genesis,
+                step.to,
+                self.steps + 1,
+            ))
         } else {
             Err(ChainError {
                 recursive_end: self.proven,
@@ -86,11 +113,43 @@ impl RecursiveMohoAttestation {
             })
         }
     }
+
+    /// Merges this recursive attestation with one covering the directly following range,
+    /// producing a recursive attestation from this genesis to the other's proven state that covers
+    /// the steps of both.
+    ///
+    /// Succeeds only if the attestations are adjacent — i.e., this proven state matches the
+    /// other's genesis.
+    ///
+    /// Returns a [`ChainError`] carrying the mismatched endpoints if there is a gap between the
+    /// two attestations.
+    #[allow(
+        clippy::result_large_err,
+        reason = "Ok variant is already the same size as ChainError, so boxing Err wouldn't shrink the Result"
+    )]
+    pub fn merge(self, next: RecursiveMohoAttestation) -> Result<Self, ChainError> {
+        if self.proven == next.genesis {
+            Ok(RecursiveMohoAttestation::new(
+                self.genesis,
+                next.proven,
+                self.steps + next.steps,
+            ))
+        } else {
+            Err(ChainError {
+                recursive_end: self.proven,
+                step_start: next.genesis,
+            })
+        }
+    }
 }
 
 impl fmt::Display for RecursiveMohoAttestation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} => {}", self.genesis, self.proven)
+        write!(
+            f,
+            "{} => {} ({} steps)",
+            self.genesis, self.proven, self.steps
+        )
     }
 }
 
@@ -255,12 +314,13 @@ mod tests {
         let s1 = state_ref(1);
         let s2 = state_ref(2);
 
-        let rec = RecursiveMohoAttestation::new(s0, s1);
+        let rec = RecursiveMohoAttestation::new(s0, s1, 1);
         let step = StepMohoAttestation::new(s1, s2);
 
         let chained = rec.chain(step).expect("continuous chain should succeed");
         assert_eq!(*chained.genesis(), s0, "genesis must be preserved");
         assert_eq!(*chained.proven(), s2, "proven must advance to step target");
+        assert_eq!(chained.steps(), 2, "chaining must count the appended step");
     }
 
     #[test]
@@ -270,7 +330,7 @@ mod tests {
         let bad_from = state_ref(3);
         let bad_to = state_ref(4);
 
-        let rec = RecursiveMohoAttestation::new(s0, s1);
+        let rec = RecursiveMohoAttestation::new(s0, s1, 1);
         let step = StepMohoAttestation::new(bad_from, bad_to);
 
         let err = rec.chain(step).expect_err("gap should cause chain to fail");
@@ -285,7 +345,7 @@ mod tests {
         let s2 = state_ref(2);
         let s3 = state_ref(3);
 
-        let rec = RecursiveMohoAttestation::new(s0, s1);
+        let rec = RecursiveMohoAttestation::new(s0, s1, 1);
 
         let rec = rec
             .chain(StepMohoAttestation::new(s1, s2))
@@ -296,6 +356,55 @@ mod tests {
 
         assert_eq!(*rec.genesis(), s0, "genesis stays fixed across all chains");
         assert_eq!(*rec.proven(), s3, "proven reaches final step target");
+        assert_eq!(rec.steps(), 3, "each chained step is counted");
+    }
+
+    #[test]
+    fn merge_adjacent_succeeds() {
+        let s0 = state_ref(0);
+        let s1 = state_ref(1);
+        let s2 = state_ref(2);
+
+        let left = RecursiveMohoAttestation::new(s0, s1, 1);
+        let right = RecursiveMohoAttestation::new(s1, s2, 1);
+
+        let merged = left.merge(right).expect("adjacent merge should succeed");
+        assert_eq!(*merged.genesis(), s0, "genesis must come from the left");
+        assert_eq!(*merged.proven(), s2, "proven must come from the right");
+        assert_eq!(merged.steps(), 2, "merging must sum the step counts");
+    }
+
+    #[test]
+    fn merge_gap_returns_err() {
+        let left = RecursiveMohoAttestation::new(state_ref(0), state_ref(1), 1);
+        let right = RecursiveMohoAttestation::new(state_ref(2), state_ref(3), 1);
+
+        let err = left
+            .merge(right)
+            .expect_err("gap should cause merge to fail");
+        assert_eq!(err.recursive_end, state_ref(1));
+        assert_eq!(err.step_start, state_ref(2));
+    }
+
+    #[test]
+    fn merge_matches_sequential_chain() {
+        let s: Vec<_> = (0..5).map(state_ref).collect();
+
+        let sequential = RecursiveMohoAttestation::new(s[0], s[1], 1)
+            .chain(StepMohoAttestation::new(s[1], s[2]))
+            .and_then(|r| r.chain(StepMohoAttestation::new(s[2], s[3])))
+            .and_then(|r| r.chain(StepMohoAttestation::new(s[3], s[4])))
+            .expect("sequential chain should succeed");
+
+        let left = RecursiveMohoAttestation::new(s[0], s[1], 1)
+            .chain(StepMohoAttestation::new(s[1], s[2]))
+            .expect("left chain should succeed");
+        let right = RecursiveMohoAttestation::new(s[2], s[3], 1)
+            .chain(StepMohoAttestation::new(s[3], s[4]))
+            .expect("right chain should succeed");
+        let merged = left.merge(right).expect("merge should succeed");
+
+        assert_eq!(merged, sequential);
     }
 
     #[test]
@@ -304,7 +413,7 @@ mod tests {
         let s1 = state_ref(1);
         let s2 = state_ref(2);
 
-        let rec = RecursiveMohoAttestation::new(s0, s1);
+        let rec = RecursiveMohoAttestation::new(s0, s1, 1);
         let rec = rec
             .chain(StepMohoAttestation::new(s1, s2))
             .expect("first chain should succeed");
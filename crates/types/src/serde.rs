@@ -4,7 +4,7 @@
 //! using different strategies based on whether the format is human-readable.
 //!
 //! ## Commitment / reference types ([`StateReference`], [`InnerStateCommitment`],
-//! [`MohoStateCommitment`])
+//! [`MohoStateCommitment`], [`MohoInstanceId`], [`SpecCommitment`])
 //!
 //! Each is a 32-byte newtype.
 //!
@@ -23,14 +23,15 @@
 //! field types; the manual `Serialize`/`Deserialize` impls branch on `is_human_readable()` and
 //! delegate to either the proxy (human-readable) or `as_ssz_bytes` / `from_ssz_bytes` (binary).
 
+use alloc::{format, vec::Vec};
 use core::fmt;
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Visitor};
 use ssz::{Decode, Encode};
 
 use crate::{
-    ExportContainer, ExportState, InnerStateCommitment, MohoState, MohoStateCommitment,
-    StateReference,
+    ExportContainer, ExportState, InnerStateCommitment, MohoInstanceId, MohoState,
+    MohoStateCommitment, SpecCommitment, StateReference,
 };
 
 // -- 32-byte newtype helpers -----------------------------------------------------------------
@@ -86,6 +87,8 @@ macro_rules! impl_id_serde {
 impl_id_serde!(StateReference);
 impl_id_serde!(InnerStateCommitment);
 impl_id_serde!(MohoStateCommitment);
+impl_id_serde!(MohoInstanceId);
+impl_id_serde!(SpecCommitment);
 
 // -- Human-readable proxy structs for the SSZ container types --------------------------------
 //
@@ -100,12 +103,18 @@ impl_id_serde!(MohoStateCommitment);
 // type or the proxy fails to compile here — keeping the two definitions in sync.
 
 mod hr {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use ssz_types::{FixedBytes, VariableList};
     use strata_merkle::Mmr64B32;
     use strata_predicate::PredicateKey;
 
-    use crate::{ExportContainer, ExportState, MohoState};
+    use crate::{ExportContainer, ExportState, MohoState, PendingPredicate};
 
     /// `FixedBytes<32>` represented as an `0x`-prefixed lowercase hex string.
     #[derive(Copy, Clone)]
@@ -214,7 +223,41 @@ mod hr {
                 .collect();
             let containers = VariableList::new(containers?)
                 .map_err(|e| format!("export state containers: {e:?}"))?;
-            Ok(ExportState { containers })
+            let state = ExportState { containers };
+            state.validate().map_err(|e| e.to_string())?;
+            Ok(state)
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct PendingPredicateProxy {
+        pub predicate: PredicateKey,
+        pub remaining_steps: u64,
+    }
+
+    impl From<&PendingPredicate> for PendingPredicateProxy {
+        fn from(p: &PendingPredicate) -> Self {
+            let PendingPredicate {
+                predicate,
+                remaining_steps,
+            } = p;
+            Self {
+                predicate: predicate.clone(),
+                remaining_steps: *remaining_steps,
+            }
+        }
+    }
+
+    impl From<PendingPredicateProxy> for PendingPredicate {
+        fn from(p: PendingPredicateProxy) -> Self {
+            let PendingPredicateProxy {
+                predicate,
+                remaining_steps,
+            } = p;
+            PendingPredicate {
+                predicate,
+                remaining_steps,
+            }
         }
     }
 
@@ -223,6 +266,8 @@ mod hr {
         pub inner_state: Hex32,
         pub next_predicate: PredicateKey,
         pub export_state: ExportStateProxy,
+        pub spec_commitment: Hex32,
+        pub pending_predicate: Option<PendingPredicateProxy>,
     }
 
     impl From<&MohoState> for MohoStateProxy {
@@ -231,11 +276,15 @@ mod hr {
                 inner_state,
                 next_predicate,
                 export_state,
+                spec_commitment,
+                pending_predicate,
             } = s;
             Self {
                 inner_state: Hex32(*inner_state),
                 next_predicate: next_predicate.clone(),
                 export_state: ExportStateProxy::from(export_state),
+                spec_commitment: Hex32(*spec_commitment),
+                pending_predicate: pending_predicate.first().map(PendingPredicateProxy::from),
             }
         }
     }
@@ -247,11 +296,22 @@ mod hr {
                 inner_state,
                 next_predicate,
                 export_state,
+                spec_commitment,
+                pending_predicate,
             } = p;
+            let pending_predicate = VariableList::new(
+                pending_predicate
+                    .map(PendingPredicate::from)
+                    .into_iter()
+                    .collect(),
+            )
+            .map_err(|e| format!("pending predicate: {e:?}"))?;
             Ok(MohoState {
                 inner_state: inner_state.0,
                 next_predicate,
                 export_state: ExportState::try_from(export_state)?,
+                spec_commitment: spec_commitment.0,
+                pending_predicate,
             })
         }
     }
@@ -310,20 +370,18 @@ impl_container_serde!(ExportContainer, hr::ExportContainerProxy);
 mod tests {
     use proptest::prelude::*;
     use ssz::Encode;
+    use ssz_types::VariableList;
     use strata_predicate::PredicateKey;
 
     use super::*;
-    use crate::relation::{StateRefAttestation, StepMohoAttestation};
+    use crate::{
+        PendingPredicate,
+        relation::{StateRefAttestation, StepMohoAttestation},
+        test_utils::always_accept,
+    };
 
     type Hash32 = [u8; 32];
 
-    fn always_accept() -> PredicateKey {
-        PredicateKey {
-            id: 1,
-            condition: vec![].try_into().unwrap(),
-        }
-    }
-
     fn predicate_strategy() -> impl Strategy<Value = PredicateKey> {
         // Restricted to valid `PredicateTypeId` variants: the human-readable serde path
         // for `PredicateKey` rejects unknown ids.
@@ -349,8 +407,12 @@ mod tests {
     }
 
     fn export_state_strategy() -> impl Strategy<Value = ExportState> {
-        prop::collection::vec(export_container_strategy(), 0..5)
-            .prop_map(|containers| ExportState::new(containers).unwrap())
+        prop::collection::vec(export_container_strategy(), 0..5).prop_map(|mut containers| {
+            // Container IDs must be unique.
+            containers.sort_by_key(|c| c.container_id());
+            containers.dedup_by_key(|c| c.container_id());
+            ExportState::new(containers).unwrap()
+        })
     }
 
     fn moho_state_strategy() -> impl Strategy<Value = MohoState> {
@@ -361,7 +423,7 @@ mod tests {
         )
             .prop_map(|(inner_bytes, predicate, export_state)| {
                 let inner = InnerStateCommitment::from(inner_bytes);
-                MohoState::new(inner, predicate, export_state)
+                MohoState::new(inner, predicate, export_state, SpecCommitment::default())
             })
     }
 
@@ -372,7 +434,13 @@ mod tests {
         container.add_entry([0x22; 32]).unwrap();
         container.update_extra_data([0xCD; 32]);
         let export = ExportState::new(vec![container]).unwrap();
-        MohoState::new(inner, always_accept(), export)
+        MohoState::new(
+            inner,
+            always_accept(),
+            export,
+            SpecCommitment::from([0x5E; 32]),
+        )
+        .with_pending_predicate(PendingPredicate::new(always_accept(), 3))
     }
 
     #[test]
@@ -408,6 +476,15 @@ mod tests {
             Some(format!("0x{}", "ab".repeat(32))).as_deref()
         );
         assert!(obj.contains_key("next_predicate"));
+        assert_eq!(
+            obj.get("spec_commitment").and_then(|v| v.as_str()),
+            Some(format!("0x{}", "5e".repeat(32))).as_deref()
+        );
+        let pending = obj.get("pending_predicate").expect("pending_predicate");
+        assert_eq!(
+            pending.get("remaining_steps").and_then(|v| v.as_u64()),
+            Some(3)
+        );
 
         let containers = obj
             .get("export_state")
@@ -458,6 +535,20 @@ mod tests {
         assert_eq!(export.as_ssz_bytes(), bin_back.as_ssz_bytes());
     }
 
+    #[test]
+    fn export_state_rejects_unsorted_containers() {
+        let unsorted = ExportState {
+            containers: VariableList::new(vec![ExportContainer::new(2), ExportContainer::new(1)])
+                .unwrap(),
+        };
+
+        let json = serde_json::to_string(&unsorted).unwrap();
+        assert!(serde_json::from_str::<ExportState>(&json).is_err());
+
+        let bin = bincode::serialize(&unsorted).unwrap();
+        assert!(bincode::deserialize::<ExportState>(&bin).is_err());
+    }
+
     #[test]
     fn export_container_roundtrip_both_formats() {
         let mut container = ExportContainer::new(3);
@@ -1,16 +1,41 @@
 //! Moho state types and SSZ-based commitment/proof helpers.
 
-use core::fmt;
-
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{cmp::Ordering, fmt};
+
+use ssz::{Decode, DecodeError};
 use ssz_generated::ssz::moho::*;
-use ssz_types::VariableList;
-use strata_merkle::{Mmr, Mmr64B32, MmrState, Sha256Hasher as MerkleHasher};
+use ssz_types::{FixedBytes, VariableList};
+use strata_merkle::{
+    BinaryMerkleTree, MerkleProofB32, Mmr, Mmr64B32, MmrState, Sha256Hasher as MerkleHasher,
+    Sha256NoPrefixHasher,
+};
 use strata_predicate::{PredicateKey, PredicateTypeId};
 use tree_hash::{Sha256Hasher, TreeHash};
 
 type Hash32 = [u8; 32];
 
-use crate::{InnerStateCommitment, MohoStateCommitment, errors::ExportStateError, ssz_generated};
+use crate::{
+    InnerStateCommitment, MohoStateCommitment, SpecCommitment, errors::ExportStateError,
+    ssz_generated,
+};
+
+/// Maximum number of containers in an [`ExportState`], i.e. the bound of its `containers` list.
+///
+/// Mirrors `MAX_EXPORT_CONTAINERS` in `moho.ssz`.
+pub(crate) const MAX_EXPORT_CONTAINERS: usize = 256;
+
+// The generated `containers` list must be bounded by `MAX_EXPORT_CONTAINERS`, which only
+// type-checks if the schema constant matches.
+const _: fn(&ExportState) -> &VariableList<ExportContainer, MAX_EXPORT_CONTAINERS> =
+    |state| &state.containers;
+
+// Every `u8` container ID must fit, see `ExportState::get_or_create_container`.
+const _: () = assert!(MAX_EXPORT_CONTAINERS > u8::MAX as usize);
 
 impl MohoState {
     /// Creates a new Moho state.
@@ -18,14 +43,23 @@ impl MohoState {
         inner_state: InnerStateCommitment,
         next_predicate: PredicateKey,
         export_state: ExportState,
+        spec_commitment: SpecCommitment,
     ) -> Self {
         Self {
             inner_state: inner_state.into_inner().into(),
             next_predicate,
             export_state,
+            spec_commitment: spec_commitment.into_inner().into(),
+            pending_predicate: VariableList::default(),
         }
     }
 
+    /// Returns the state with `pending` scheduled to replace the next predicate.
+    pub fn with_pending_predicate(mut self, pending: PendingPredicate) -> Self {
+        self.pending_predicate = pending_predicate_list(Some(pending));
+        self
+    }
+
     /// Returns the inner state commitment.
     pub fn inner_state(&self) -> InnerStateCommitment {
         InnerStateCommitment::from(self.inner_state.0)
@@ -41,6 +75,16 @@ impl MohoState {
         &self.export_state
     }
 
+    /// Returns the commitment to the spec the state was produced under.
+    pub fn spec_commitment(&self) -> SpecCommitment {
+        SpecCommitment::from(self.spec_commitment.0)
+    }
+
+    /// Returns the predicate key scheduled to replace the next predicate, if any.
+    pub fn pending_predicate(&self) -> Option<&PendingPredicate> {
+        self.pending_predicate.first()
+    }
+
     /// Consumes self and returns the export state.
     pub fn into_export_state(self) -> ExportState {
         self.export_state
@@ -50,6 +94,200 @@ impl MohoState {
     pub fn compute_commitment(&self) -> MohoStateCommitment {
         MohoStateCommitment::from(<_ as TreeHash>::tree_hash_root::<Sha256Hasher>(self))
     }
+
+    /// Returns the tree hash roots of all fields, in SSZ container order.
+    ///
+    /// These are the leaves of the Merkle tree whose root is [`Self::compute_commitment`].
+    fn field_roots(&self) -> [Hash32; MohoStateField::COUNT] {
+        // Destructure exhaustively so that adding a field to `moho.ssz` fails to compile here
+        // until it is also added to `MohoStateField`.
+        let Self {
+            inner_state,
+            next_predicate,
+            export_state,
+            spec_commitment,
+            pending_predicate,
+        } = self;
+        [
+            <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(inner_state).into_inner(),
+            <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(next_predicate).into_inner(),
+            <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(export_state).into_inner(),
+            <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(spec_commitment).into_inner(),
+            <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(pending_predicate).into_inner(),
+        ]
+    }
+
+    /// Creates a Merkle inclusion proof for the given field against
+    /// [`Self::compute_commitment`].
+    pub fn prove_field(&self, field: MohoStateField) -> MerkleProofB32 {
+        let mut leaves = self.field_roots().to_vec();
+        leaves.resize(MohoStateField::COUNT.next_power_of_two(), Hash32::default());
+
+        let proof = BinaryMerkleTree::from_leaves::<Sha256NoPrefixHasher>(leaves)
+            .expect("moho state: field tree is a power of two")
+            .gen_proof(field.index())
+            .expect("moho state: field index within tree");
+        MerkleProofB32::from_generic(&proof)
+    }
+
+    /// Creates a Merkle inclusion proof for [`Self::inner_state`].
+    pub fn prove_inner_state(&self) -> MerkleProofB32 {
+        self.prove_field(MohoStateField::InnerState)
+    }
+
+    /// Creates a Merkle inclusion proof for [`Self::next_predicate`].
+    pub fn prove_next_predicate(&self) -> MerkleProofB32 {
+        self.prove_field(MohoStateField::NextPredicate)
+    }
+
+    /// Creates a Merkle inclusion proof for [`Self::export_state`].
+    pub fn prove_export_state(&self) -> MerkleProofB32 {
+        self.prove_field(MohoStateField::ExportState)
+    }
+
+    /// Creates a Merkle inclusion proof for [`Self::spec_commitment`].
+    pub fn prove_spec_commitment(&self) -> MerkleProofB32 {
+        self.prove_field(MohoStateField::SpecCommitment)
+    }
+
+    /// Creates a Merkle inclusion proof for [`Self::pending_predicate`].
+    pub fn prove_pending_predicate(&self) -> MerkleProofB32 {
+        self.prove_field(MohoStateField::PendingPredicate)
+    }
+
+    /// Verifies that `field_root` is the tree hash root of `field` in the Moho state committed
+    /// to by `commitment`.
+    pub fn verify_field(
+        commitment: &MohoStateCommitment,
+        field: MohoStateField,
+        field_root: &Hash32,
+        proof: &MerkleProofB32,
+    ) -> bool {
+        proof.index == field.index() as u64
+            && proof.cohashes.len() == MohoStateField::TREE_DEPTH
+            && proof.verify_with_root::<Sha256NoPrefixHasher>(commitment.inner(), field_root)
+    }
+
+    /// Verifies that `inner_state` is the inner state of the Moho state committed to by
+    /// `commitment`.
+    pub fn verify_inner_state(
+        commitment: &MohoStateCommitment,
+        inner_state: &InnerStateCommitment,
+        proof: &MerkleProofB32,
+    ) -> bool {
+        let leaf = FixedBytes::<32>::from(inner_state.into_inner());
+        let root = <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(&leaf).into_inner();
+        Self::verify_field(commitment, MohoStateField::InnerState, &root, proof)
+    }
+
+    /// Verifies that `predicate` is the next predicate of the Moho state committed to by
+    /// `commitment`.
+    pub fn verify_next_predicate(
+        commitment: &MohoStateCommitment,
+        predicate: &PredicateKey,
+        proof: &MerkleProofB32,
+    ) -> bool {
+        let root = <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(predicate).into_inner();
+        Self::verify_field(commitment, MohoStateField::NextPredicate, &root, proof)
+    }
+
+    /// Verifies that `export_state` is the export state of the Moho state committed to by
+    /// `commitment`.
+    pub fn verify_export_state(
+        commitment: &MohoStateCommitment,
+        export_state: &ExportState,
+        proof: &MerkleProofB32,
+    ) -> bool {
+        let root = <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(export_state).into_inner();
+        Self::verify_field(commitment, MohoStateField::ExportState, &root, proof)
+    }
+
+    /// Verifies that `spec_commitment` is the spec commitment of the Moho state committed to by
+    /// `commitment`.
+    pub fn verify_spec_commitment(
+        commitment: &MohoStateCommitment,
+        spec_commitment: &SpecCommitment,
+        proof: &MerkleProofB32,
+    ) -> bool {
+        let leaf = FixedBytes::<32>::from(spec_commitment.into_inner());
+        let root = <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(&leaf).into_inner();
+        Self::verify_field(commitment, MohoStateField::SpecCommitment, &root, proof)
+    }
+
+    /// Verifies that `pending` is the pending predicate of the Moho state committed to by
+    /// `commitment`, where `None` proves that no predicate is pending.
+    pub fn verify_pending_predicate(
+        commitment: &MohoStateCommitment,
+        pending: Option<&PendingPredicate>,
+        proof: &MerkleProofB32,
+    ) -> bool {
+        let list = pending_predicate_list(pending.cloned());
+        let root = <_ as TreeHash>::tree_hash_root::<Sha256Hasher>(&list).into_inner();
+        Self::verify_field(commitment, MohoStateField::PendingPredicate, &root, proof)
+    }
+}
+
+/// Builds the SSZ list holding the optional pending predicate of a [`MohoState`].
+fn pending_predicate_list(pending: Option<PendingPredicate>) -> VariableList<PendingPredicate, 1> {
+    VariableList::new(pending.into_iter().collect())
+        .expect("moho state: at most one pending predicate")
+}
+
+impl PendingPredicate {
+    /// Creates a pending predicate activating `predicate` after `remaining_steps` further steps.
+    pub fn new(predicate: PredicateKey, remaining_steps: u64) -> Self {
+        Self {
+            predicate,
+            remaining_steps,
+        }
+    }
+
+    /// Returns the predicate key to activate.
+    pub fn predicate(&self) -> &PredicateKey {
+        &self.predicate
+    }
+
+    /// Returns the number of further steps after which the predicate becomes the next predicate.
+    pub fn remaining_steps(&self) -> u64 {
+        self.remaining_steps
+    }
+
+    /// Consumes self and returns the predicate key to activate.
+    pub fn into_predicate(self) -> PredicateKey {
+        self.predicate
+    }
+}
+
+/// A field of [`MohoState`] that can be proven against its [`MohoStateCommitment`].
+///
+/// Variants are declared in the same order as the fields of the `MohoState` SSZ container, so
+/// the discriminant is the index of the field's leaf in the container's Merkle tree.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MohoStateField {
+    /// [`MohoState::inner_state`].
+    InnerState,
+    /// [`MohoState::next_predicate`].
+    NextPredicate,
+    /// [`MohoState::export_state`].
+    ExportState,
+    /// [`MohoState::spec_commitment`].
+    SpecCommitment,
+    /// [`MohoState::pending_predicate`].
+    PendingPredicate,
+}
+
+impl MohoStateField {
+    /// Number of fields in the `MohoState` SSZ container.
+    pub const COUNT: usize = 5;
+
+    /// Depth of the `MohoState` container Merkle tree, i.e. the number of cohashes in a field
+    /// proof.
+    pub const TREE_DEPTH: usize = Self::COUNT.next_power_of_two().trailing_zeros() as usize;
+
+    /// Returns the index of this field's leaf in the `MohoState` container Merkle tree.
+    pub fn index(self) -> usize {
+        self as usize
+    }
 }
 
 impl fmt::Display for MohoState {
@@ -73,8 +311,10 @@ impl fmt::Display for MohoState {
         write!(
             f,
             "MohoState {{ inner_state: {inner}, predicate: {predicate_type}(0x{condition_hex}), \
-             export_containers: {} }}",
-            self.export_state().containers().len()
+             export_containers: {}, spec_commitment: {}, pending_predicate: {} }}",
+            self.export_state().containers().len(),
+            self.spec_commitment(),
+            self.pending_predicate().is_some()
         )
     }
 }
@@ -82,16 +322,83 @@ impl fmt::Display for MohoState {
 // Compatibility constructors and accessors for SSZ-generated types
 impl ExportState {
     /// Creates a new export state with the given containers.
-    pub fn new(containers: Vec<ExportContainer>) -> Result<Self, ExportStateError> {
+    ///
+    /// Containers are kept sorted by ID, so that export states holding the same containers have
+    /// the same commitment regardless of the order they were given in.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportStateError::DuplicateContainerId` if two containers share an ID.
+    pub fn new(mut containers: Vec<ExportContainer>) -> Result<Self, ExportStateError> {
+        containers.sort_by_key(|c| c.container_id);
+        if let Some(pair) = containers
+            .windows(2)
+            .find(|pair| pair[0].container_id == pair[1].container_id)
+        {
+            return Err(ExportStateError::DuplicateContainerId(pair[0].container_id));
+        }
         let containers = VariableList::new(containers)?;
         Ok(Self { containers })
     }
 
+    /// Checks that the container IDs are unique and sorted.
+    ///
+    /// Export states built through [`Self::new`], the mutating methods and SSZ decoding always
+    /// satisfy this.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExportStateError::DuplicateContainerId` if two containers share an ID, or
+    /// `ExportStateError::UnsortedContainers` if the containers are not sorted by ID.
+    pub fn validate(&self) -> Result<(), ExportStateError> {
+        for pair in self.containers.windows(2) {
+            match pair[0].container_id.cmp(&pair[1].container_id) {
+                Ordering::Less => {}
+                Ordering::Equal => {
+                    return Err(ExportStateError::DuplicateContainerId(pair[0].container_id));
+                }
+                Ordering::Greater => return Err(ExportStateError::UnsortedContainers),
+            }
+        }
+        Ok(())
+    }
+
     /// Returns a slice of all containers.
     pub fn containers(&self) -> &[ExportContainer] {
         &self.containers
     }
 
+    /// Returns the container with the specified ID, if it exists.
+    pub fn get_container(&self, container_id: u8) -> Option<&ExportContainer> {
+        self.position(container_id)
+            .ok()
+            .map(|index| &self.containers[index])
+    }
+
+    /// Searches the container with the specified ID, returning its index if it exists, or the
+    /// index it must be inserted at to keep the containers sorted otherwise.
+    pub(crate) fn position(&self, container_id: u8) -> Result<usize, usize> {
+        self.containers
+            .binary_search_by_key(&container_id, |c| c.container_id)
+    }
+
+    /// Returns the container with the specified ID, creating an empty one if it does not exist.
+    fn get_or_create_container(&mut self, container_id: u8) -> &mut ExportContainer {
+        let index = match self.position(container_id) {
+            Ok(index) => index,
+            Err(index) => {
+                // SAFETY: MAX_EXPORT_CONTAINERS = 256 matches the full range of u8 (0-255),
+                // so we can never exceed capacity with unique container_ids
+                self.containers
+                    .push(ExportContainer::new(container_id))
+                    .expect("container capacity should never be exceeded with u8 container_id");
+                self.containers[index..].rotate_right(1);
+                index
+            }
+        };
+        &mut self.containers[index]
+    }
+
     /// Adds an entry to the container with the specified ID.
     ///
     /// If a container with the given `container_id` exists, the entry is appended to its MMR.
@@ -107,25 +414,46 @@ impl ExportState {
     /// matches the full range of `u8` container IDs (0-255). Since each container_id is unique,
     /// we can never exceed the container list capacity.
     pub fn add_entry(&mut self, container_id: u8, entry: Hash32) -> Result<(), ExportStateError> {
-        if let Some(container) = self
-            .containers
-            .iter_mut()
-            .find(|c| c.container_id == container_id)
-        {
-            container.add_entry(entry)?;
-        } else {
-            let mut new_container = ExportContainer::new(container_id);
-            new_container.add_entry(entry)?;
-            // SAFETY: MAX_EXPORT_CONTAINERS = 256 matches the full range of u8 (0-255),
-            // so we can never exceed capacity with unique container_ids
-            self.containers
-                .push(new_container)
-                .expect("container capacity should never be exceeded with u8 container_id");
-        }
-        Ok(())
+        // An empty MMR always has room for an entry, so this never leaves an empty container
+        // behind.
+        self.get_or_create_container(container_id).add_entry(entry)
+    }
+
+    /// Updates the extra data of the container with the specified ID.
+    ///
+    /// If no container exists with that ID, a new container is created with the given extra data.
+    pub fn update_extra_data(&mut self, container_id: u8, extra_data: Hash32) {
+        self.get_or_create_container(container_id)
+            .update_extra_data(extra_data);
+    }
+}
+
+/// Decodes an export state, rejecting ones whose container IDs are not unique and sorted.
+impl Decode for ExportState {
+    fn is_ssz_fixed_len() -> bool {
+        <UncheckedExportState as Decode>::is_ssz_fixed_len()
+    }
+
+    fn ssz_fixed_len() -> usize {
+        <UncheckedExportState as Decode>::ssz_fixed_len()
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let UncheckedExportState { containers } = UncheckedExportState::from_ssz_bytes(bytes)?;
+        let state = Self { containers };
+        state
+            .validate()
+            .map_err(|e| DecodeError::BytesInvalid(format!("invalid export state: {e}")))?;
+        Ok(state)
     }
 }
 
+/// The SSZ layout of an [`ExportState`], decoded without checking its invariants.
+#[derive(ssz_derive::Decode)]
+struct UncheckedExportState {
+    containers: VariableList<ExportContainer, MAX_EXPORT_CONTAINERS>,
+}
+
 impl ExportContainer {
     /// Creates a new export container with an empty MMR and default empty/zeroed out extra data.
     pub fn new(container_id: u8) -> Self {
@@ -175,14 +503,7 @@ mod tests {
     use tree_hash::Sha256Hasher;
 
     use super::*;
-
-    /// Helper function to create an always_accept predicate
-    fn always_accept() -> PredicateKey {
-        PredicateKey {
-            id: 1, // AlwaysAccept ID
-            condition: vec![].try_into().unwrap(),
-        }
-    }
+    use crate::test_utils::always_accept;
 
     fn predicate_strategy() -> impl Strategy<Value = PredicateKey> {
         (any::<u8>(), prop::collection::vec(any::<u8>(), 0..8)).prop_map(|(id, condition)| {
@@ -206,8 +527,12 @@ mod tests {
     }
 
     fn export_state_strategy() -> impl Strategy<Value = ExportState> {
-        prop::collection::vec(export_container_strategy(), 0..5)
-            .prop_map(|containers| ExportState::new(containers).unwrap())
+        prop::collection::vec(export_container_strategy(), 0..5).prop_map(|mut containers| {
+            // Container IDs must be unique.
+            containers.sort_by_key(|c| c.container_id());
+            containers.dedup_by_key(|c| c.container_id());
+            ExportState::new(containers).unwrap()
+        })
     }
 
     fn moho_state_strategy() -> impl Strategy<Value = MohoState> {
@@ -215,11 +540,23 @@ mod tests {
             any::<Hash32>(),
             predicate_strategy(),
             export_state_strategy(),
+            any::<Hash32>(),
+            prop::option::of((predicate_strategy(), any::<u64>())),
         )
-            .prop_map(|(inner_bytes, predicate, export_state)| {
-                let inner_state = InnerStateCommitment::from(inner_bytes);
-                MohoState::new(inner_state, predicate, export_state)
-            })
+            .prop_map(
+                |(inner_bytes, predicate, export_state, spec_bytes, pending)| {
+                    let inner_state = InnerStateCommitment::from(inner_bytes);
+                    let spec_commitment = SpecCommitment::from(spec_bytes);
+                    let state =
+                        MohoState::new(inner_state, predicate, export_state, spec_commitment);
+                    match pending {
+                        Some((predicate, steps)) => {
+                            state.with_pending_predicate(PendingPredicate::new(predicate, steps))
+                        }
+                        None => state,
+                    }
+                },
+            )
     }
 
     mod export_container_tests {
@@ -306,6 +643,111 @@ mod tests {
             assert_ne!(containers[0].entries_mmr(), &initial_mmr);
         }
 
+        #[test]
+        fn test_new_sorts_containers() {
+            let state = ExportState::new(vec![
+                ExportContainer::new(9),
+                ExportContainer::new(1),
+                ExportContainer::new(4),
+            ])
+            .unwrap();
+            let ids: Vec<u8> = state
+                .containers()
+                .iter()
+                .map(|c| c.container_id())
+                .collect();
+            assert_eq!(ids, [1, 4, 9]);
+            assert_eq!(state.validate(), Ok(()));
+
+            // The commitment does not depend on the order containers were given in.
+            let reordered = ExportState::new(vec![
+                ExportContainer::new(4),
+                ExportContainer::new(9),
+                ExportContainer::new(1),
+            ])
+            .unwrap();
+            assert_eq!(
+                <ExportState as TreeHash>::tree_hash_root::<Sha256Hasher>(&state),
+                <ExportState as TreeHash>::tree_hash_root::<Sha256Hasher>(&reordered)
+            );
+        }
+
+        #[test]
+        fn test_new_rejects_duplicate_ids() {
+            let res = ExportState::new(vec![
+                ExportContainer::new(3),
+                ExportContainer::new(1),
+                ExportContainer::new(3),
+            ]);
+            assert_eq!(res, Err(ExportStateError::DuplicateContainerId(3)));
+        }
+
+        #[test]
+        fn test_decode_rejects_violations() {
+            let export_state = |ids: &[u8]| {
+                let containers: Vec<_> = ids.iter().map(|id| ExportContainer::new(*id)).collect();
+                ExportState {
+                    containers: VariableList::new(containers).unwrap(),
+                }
+            };
+
+            let unsorted = export_state(&[2, 1]);
+            assert_eq!(
+                unsorted.validate(),
+                Err(ExportStateError::UnsortedContainers)
+            );
+            assert!(ExportState::from_ssz_bytes(&unsorted.as_ssz_bytes()).is_err());
+
+            let duplicate = export_state(&[1, 2, 2]);
+            assert_eq!(
+                duplicate.validate(),
+                Err(ExportStateError::DuplicateContainerId(2))
+            );
+            assert!(ExportState::from_ssz_bytes(&duplicate.as_ssz_bytes()).is_err());
+
+            // Moho states embedding them are rejected too.
+            let state = MohoState::new(
+                InnerStateCommitment::from([0x11; 32]),
+                always_accept(),
+                ExportState::new(vec![]).unwrap(),
+                SpecCommitment::default(),
+            );
+            let state = MohoState {
+                export_state: unsorted,
+                ..state
+            };
+            assert!(MohoState::from_ssz_bytes(&state.as_ssz_bytes()).is_err());
+
+            let sorted = export_state(&[1, 2]);
+            assert_eq!(
+                ExportState::from_ssz_bytes(&sorted.as_ssz_bytes()),
+                Ok(sorted)
+            );
+        }
+
+        #[test]
+        fn test_add_entry_keeps_containers_sorted() {
+            let mut state = ExportState::new(vec![]).unwrap();
+            for id in [5, 2, 8, 2, 0] {
+                state.add_entry(id, [id; 32]).unwrap();
+            }
+            state.update_extra_data(3, [0x33; 32]);
+
+            let ids: Vec<u8> = state
+                .containers()
+                .iter()
+                .map(|c| c.container_id())
+                .collect();
+            assert_eq!(ids, [0, 2, 3, 5, 8]);
+            assert_eq!(state.validate(), Ok(()));
+            assert_eq!(
+                state.get_container(2).unwrap().entries_mmr().num_entries(),
+                2
+            );
+            assert_eq!(state.get_container(3).unwrap().extra_data(), &[0x33; 32]);
+            assert!(state.get_container(4).is_none());
+        }
+
         #[test]
         fn test_add_entry_creates_container() {
             let mut state = ExportState::new(vec![]).unwrap();
@@ -323,6 +765,14 @@ mod tests {
     mod moho_state_tests {
         use super::*;
 
+        const ALL_FIELDS: [MohoStateField; MohoStateField::COUNT] = [
+            MohoStateField::InnerState,
+            MohoStateField::NextPredicate,
+            MohoStateField::ExportState,
+            MohoStateField::SpecCommitment,
+            MohoStateField::PendingPredicate,
+        ];
+
         proptest! {
             #[test]
             fn ssz_roundtrip(state in moho_state_strategy()) {
@@ -349,6 +799,36 @@ mod tests {
                 prop_assert_eq!(hash1, hash2);
             }
 
+            #[test]
+            fn field_proofs_verify(state in moho_state_strategy()) {
+                let commitment = state.compute_commitment();
+                prop_assert!(MohoState::verify_inner_state(
+                    &commitment,
+                    &state.inner_state(),
+                    &state.prove_inner_state(),
+                ));
+                prop_assert!(MohoState::verify_next_predicate(
+                    &commitment,
+                    state.next_predicate(),
+                    &state.prove_next_predicate(),
+                ));
+                prop_assert!(MohoState::verify_export_state(
+                    &commitment,
+                    state.export_state(),
+                    &state.prove_export_state(),
+                ));
+                prop_assert!(MohoState::verify_spec_commitment(
+                    &commitment,
+                    &state.spec_commitment(),
+                    &state.prove_spec_commitment(),
+                ));
+                prop_assert!(MohoState::verify_pending_predicate(
+                    &commitment,
+                    state.pending_predicate(),
+                    &state.prove_pending_predicate(),
+                ));
+            }
+
             #[test]
             fn commitment_deterministic(state in moho_state_strategy()) {
                 let commitment1 = state.compute_commitment();
@@ -364,7 +844,7 @@ mod tests {
             let inner = InnerStateCommitment::from(Hash32::default());
             let predicate = always_accept();
             let export = ExportState::new(vec![]).unwrap();
-            let state = MohoState::new(inner, predicate, export);
+            let state = MohoState::new(inner, predicate, export, SpecCommitment::default());
 
             print!("{}", state);
 
@@ -383,7 +863,7 @@ mod tests {
             let inner = InnerStateCommitment::from([0xAB; 32]);
             let predicate = always_accept();
             let export = ExportState::new(vec![]).unwrap();
-            let state = MohoState::new(inner, predicate, export);
+            let state = MohoState::new(inner, predicate, export, SpecCommitment::default());
 
             let encoded = state.as_ssz_bytes();
             let decoded = MohoState::from_ssz_bytes(&encoded).unwrap();
@@ -411,7 +891,7 @@ mod tests {
             container2.add_entry(entry3).unwrap();
 
             let export = ExportState::new(vec![container1, container2]).unwrap();
-            let state = MohoState::new(inner, predicate, export);
+            let state = MohoState::new(inner, predicate, export, SpecCommitment::default());
 
             let encoded = state.as_ssz_bytes();
             let decoded = MohoState::from_ssz_bytes(&encoded).unwrap();
@@ -439,13 +919,28 @@ mod tests {
             let predicate = always_accept();
             let export = ExportState::new(vec![]).unwrap();
 
-            let state1 = MohoState::new(inner1, predicate.clone(), export.clone());
-            let state2 = MohoState::new(inner2, predicate, export);
+            let state1 = MohoState::new(
+                inner1,
+                predicate.clone(),
+                export.clone(),
+                SpecCommitment::default(),
+            );
+            let state2 = MohoState::new(
+                inner2,
+                predicate.clone(),
+                export.clone(),
+                SpecCommitment::default(),
+            );
+            let state3 =
+                MohoState::new(inner1, predicate, export, SpecCommitment::from([0x03; 32]));
 
             let commitment1 = state1.compute_commitment();
             let commitment2 = state2.compute_commitment();
+            let commitment3 = state3.compute_commitment();
 
             assert_ne!(commitment1.into_inner(), commitment2.into_inner());
+            // A state produced under another spec commits differently.
+            assert_ne!(commitment1.into_inner(), commitment3.into_inner());
         }
 
         #[test]
@@ -453,11 +948,97 @@ mod tests {
             let inner = InnerStateCommitment::from([0xCD; 32]);
             let predicate = always_accept();
             let export = ExportState::new(vec![]).unwrap();
-            let state = MohoState::new(inner, predicate.clone(), export);
+            let state = MohoState::new(inner, predicate.clone(), export, SpecCommitment::default());
 
             assert_eq!(state.inner_state().inner(), &[0xCD; 32]);
             assert_eq!(state.next_predicate(), &predicate);
             assert_eq!(state.export_state().containers().len(), 0);
+            assert_eq!(state.spec_commitment(), SpecCommitment::default());
+        }
+
+        #[test]
+        fn test_field_proof_rejects_wrong_field() {
+            let state = MohoState::new(
+                InnerStateCommitment::from([0x42; 32]),
+                always_accept(),
+                ExportState::new(vec![ExportContainer::new(3)]).unwrap(),
+                SpecCommitment::default(),
+            );
+            let commitment = state.compute_commitment();
+            let roots = state.field_roots();
+
+            for field in ALL_FIELDS {
+                let proof = state.prove_field(field);
+                for other in ALL_FIELDS {
+                    let verified =
+                        MohoState::verify_field(&commitment, other, &roots[other.index()], &proof);
+                    assert_eq!(
+                        verified,
+                        field == other,
+                        "{field:?} proof against {other:?}"
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_field_proof_rejects_wrong_value() {
+            let state = MohoState::new(
+                InnerStateCommitment::from([0x42; 32]),
+                always_accept(),
+                ExportState::new(vec![]).unwrap(),
+                SpecCommitment::default(),
+            );
+            let commitment = state.compute_commitment();
+
+            assert!(!MohoState::verify_inner_state(
+                &commitment,
+                &InnerStateCommitment::from([0x43; 32]),
+                &state.prove_inner_state(),
+            ));
+            assert!(!MohoState::verify_export_state(
+                &commitment,
+                &ExportState::new(vec![ExportContainer::new(1)]).unwrap(),
+                &state.prove_export_state(),
+            ));
+            assert!(!MohoState::verify_spec_commitment(
+                &commitment,
+                &SpecCommitment::from([0x01; 32]),
+                &state.prove_spec_commitment(),
+            ));
+            assert!(!MohoState::verify_pending_predicate(
+                &commitment,
+                Some(&PendingPredicate::new(always_accept(), 1)),
+                &state.prove_pending_predicate(),
+            ));
+        }
+
+        #[test]
+        fn test_pending_predicate() {
+            let state = MohoState::new(
+                InnerStateCommitment::from([0x42; 32]),
+                always_accept(),
+                ExportState::new(vec![]).unwrap(),
+                SpecCommitment::default(),
+            );
+            assert!(state.pending_predicate().is_none());
+
+            let pending = PendingPredicate::new(always_accept(), 3);
+            let upgrading = state.clone().with_pending_predicate(pending.clone());
+            assert_eq!(upgrading.pending_predicate(), Some(&pending));
+            assert_eq!(upgrading.pending_predicate().unwrap().remaining_steps(), 3);
+
+            // The pending predicate is bound into the commitment, so light clients can see it.
+            assert_ne!(state.compute_commitment(), upgrading.compute_commitment());
+            let decoded = MohoState::from_ssz_bytes(&upgrading.as_ssz_bytes()).unwrap();
+            assert_eq!(decoded.pending_predicate(), Some(&pending));
+
+            // Proofs of the pending predicate do not verify against the other state.
+            assert!(!MohoState::verify_pending_predicate(
+                &upgrading.compute_commitment(),
+                None,
+                &state.prove_pending_predicate(),
+            ));
         }
 
         #[test]
@@ -466,7 +1047,7 @@ mod tests {
             let predicate = always_accept();
             let container = ExportContainer::new(1);
             let export = ExportState::new(vec![container]).unwrap();
-            let state = MohoState::new(inner, predicate, export);
+            let state = MohoState::new(inner, predicate, export, SpecCommitment::default());
 
             let extracted_export = state.into_export_state();
             assert_eq!(extracted_export.containers().len(), 1);
@@ -0,0 +1,13 @@
+//! Test utilities shared by the Moho crates.
+
+use alloc::vec::Vec;
+
+use strata_predicate::PredicateKey;
+
+/// Returns the always-accept predicate key, under which any proof verifies.
+pub fn always_accept() -> PredicateKey {
+    PredicateKey {
+        id: 1, // AlwaysAccept ID
+        condition: Vec::new().try_into().unwrap(),
+    }
+}